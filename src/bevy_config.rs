@@ -1,3 +1,4 @@
+use crate::file_system_interaction::replay::ReplayCheck;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
 use bevy::window::PresentMode;
@@ -22,6 +23,8 @@ pub(crate) fn bevy_config_plugin(app: &mut App) {
             #[cfg(not(feature = "wasm"))]
             mode: WindowMode::BorderlessFullscreen,
             fit_canvas_to_parent: true,
+            // Replay checks run unattended
+            visible: ReplayCheck::from_args().is_none(),
             ..default()
         }),
         ..default()
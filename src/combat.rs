@@ -1,2 +1,3 @@
 pub(crate) mod shoot;
-pub(crate) mod health;
\ No newline at end of file
+pub(crate) mod health;
+pub(crate) mod hazards;
\ No newline at end of file
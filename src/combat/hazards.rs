@@ -0,0 +1,261 @@
+use crate::combat::health::{Health, Invulnerable};
+use crate::movement::general_movement::{GeneralMovementSystemSet, Grounded};
+use crate::movement::physics::SensorVolumeReader;
+use crate::simulation::SimulationSet;
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use oxidized_navigation::NavMeshSettings;
+use serde::{Deserialize, Serialize};
+
+/// Handles the ways the level itself can hurt characters: falling from great heights, entering a [`Killzone`],
+/// and falling out of the world below [`NavMeshSettings::world_bottom_bound`].
+/// Characters with [`Health`] die, while [`Respawnable`] characters like the player are put back onto solid ground.
+pub(crate) fn hazard_plugin(app: &mut App) {
+    app.register_type::<FallDamage>()
+        .register_type::<Killzone>()
+        .register_type::<Respawnable>()
+        .add_event::<Landed>()
+        .add_system(
+            read_killzones
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_systems(
+            (
+                apply_fall_damage,
+                apply_killzones,
+                catch_out_of_bounds,
+                track_safe_positions,
+            )
+                .chain()
+                .after(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// Damages a character's [`Health`] when it lands faster than [`FallDamage::min_speed`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct FallDamage {
+    /// Landing speed in m/s up to which no damage is taken
+    pub(crate) min_speed: f32,
+    /// Damage per m/s of landing speed above [`FallDamage::min_speed`]
+    pub(crate) damage_per_speed: f32,
+    /// Highest downward speed reached since leaving the ground in m/s
+    pub(crate) peak_fall_speed: f32,
+}
+
+impl Default for FallDamage {
+    fn default() -> Self {
+        Self {
+            min_speed: 10.0,
+            damage_per_speed: 10.0,
+            peak_fall_speed: 0.0,
+        }
+    }
+}
+
+/// Sent by characters with [`FallDamage`] when they touch the ground after falling, whether they were hurt or not.
+/// Not sent after being teleported by [`kill_or_respawn`], since that forgets the fall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Landed {
+    pub(crate) entity: Entity,
+    /// See [`FallDamage::peak_fall_speed`]
+    pub(crate) fall_speed: f32,
+}
+
+/// A volume that kills or respawns every character entering it, e.g. a lava pit or a bottomless chasm.
+/// Authored in the level by tagging an object with `[killzone]`. Its meshes become invisible convex sensor colliders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Killzone;
+
+/// Instead of dying, this character is teleported back to the last place it was safely standing on when it runs into a hazard.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Respawnable {
+    pub(crate) last_grounded_position: Option<Vec3>,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_killzones(mut sensor_volumes: SensorVolumeReader) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_killzones").entered();
+    sensor_volumes.read("[killzone]", |_points| Killzone)?;
+    Ok(())
+}
+
+fn apply_fall_damage(
+    mut characters: Query<(
+        Entity,
+        &Velocity,
+        &Transform,
+        &Grounded,
+        &mut FallDamage,
+        &mut Health,
+        Option<&Invulnerable>,
+    )>,
+    mut landings: EventWriter<Landed>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_fall_damage").entered();
+    for (entity, velocity, transform, grounded, mut fall_damage, mut health, invulnerable) in
+        &mut characters
+    {
+        if !grounded.is_grounded() {
+            let fall_speed = -velocity.linvel.dot(transform.up());
+            fall_damage.peak_fall_speed = fall_damage.peak_fall_speed.max(fall_speed);
+            continue;
+        }
+        if fall_damage.peak_fall_speed > 0.0 {
+            landings.send(Landed {
+                entity,
+                fall_speed: fall_damage.peak_fall_speed,
+            });
+        }
+        let excess_speed = fall_damage.peak_fall_speed - fall_damage.min_speed;
+        fall_damage.peak_fall_speed = 0.0;
+        if excess_speed > 0.0 {
+            health.take_damage(excess_speed * fall_damage.damage_per_speed, invulnerable);
+        }
+    }
+}
+
+fn apply_killzones(
+    killzones: Query<Entity, With<Killzone>>,
+    mut characters: Query<(
+        &mut Transform,
+        &mut Velocity,
+        Option<&Respawnable>,
+        Option<&mut Health>,
+        Option<&mut FallDamage>,
+        Option<&Invulnerable>,
+    )>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_killzones").entered();
+    for killzone in &killzones {
+        for (collider_a, collider_b, intersecting) in rapier_context.intersections_with(killzone) {
+            if !intersecting {
+                continue;
+            }
+            let other = if collider_a == killzone {
+                collider_b
+            } else {
+                collider_a
+            };
+            if let Ok((
+                mut transform,
+                mut velocity,
+                respawnable,
+                health,
+                fall_damage,
+                invulnerable,
+            )) = characters.get_mut(other)
+            {
+                kill_or_respawn(
+                    &mut transform,
+                    &mut velocity,
+                    respawnable,
+                    health,
+                    fall_damage,
+                    invulnerable,
+                );
+            }
+        }
+    }
+}
+
+/// The safety net for everything that falls through the level without hitting a [`Killzone`].
+fn catch_out_of_bounds(
+    mut characters: Query<(
+        &mut Transform,
+        &mut Velocity,
+        Option<&Respawnable>,
+        Option<&mut Health>,
+        Option<&mut FallDamage>,
+        Option<&Invulnerable>,
+    )>,
+    nav_mesh_settings: Res<NavMeshSettings>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("catch_out_of_bounds").entered();
+    for (mut transform, mut velocity, respawnable, health, fall_damage, invulnerable) in
+        &mut characters
+    {
+        if transform.translation.y < nav_mesh_settings.world_bottom_bound {
+            kill_or_respawn(
+                &mut transform,
+                &mut velocity,
+                respawnable,
+                health,
+                fall_damage,
+                invulnerable,
+            );
+        }
+    }
+}
+
+fn track_safe_positions(
+    mut characters: Query<(Entity, &Transform, &Grounded, &mut Respawnable)>,
+    killzones: Query<(), With<Killzone>>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("track_safe_positions").entered();
+    for (entity, transform, grounded, mut respawnable) in &mut characters {
+        // Moving platforms will have moved away by the time we want to respawn
+        let is_on_static_ground =
+            grounded.is_grounded() && grounded.ground_velocity == Vec3::ZERO;
+        let is_in_killzone = rapier_context
+            .intersections_with(entity)
+            .any(|(a, b, intersecting)| {
+                intersecting && (killzones.contains(a) || killzones.contains(b))
+            });
+        if is_on_static_ground && !is_in_killzone {
+            respawnable.last_grounded_position = Some(transform.translation);
+        }
+    }
+}
+
+/// Teleports [`Respawnable`] characters back to where they last stood safely and kills everything else,
+/// including respawnable characters that have not stood anywhere safe yet.
+pub(crate) fn kill_or_respawn(
+    transform: &mut Transform,
+    velocity: &mut Velocity,
+    respawnable: Option<&Respawnable>,
+    health: Option<Mut<Health>>,
+    fall_damage: Option<Mut<FallDamage>>,
+    invulnerable: Option<&Invulnerable>,
+) {
+    let respawned = respawnable.map_or(false, |respawnable| {
+        respawn(transform, velocity, respawnable, fall_damage)
+    });
+    if !respawned && let Some(mut health) = health {
+        health.kill(invulnerable);
+    }
+}
+
+/// Puts the character back where it last stood safely. Returns `false` without doing anything if no such place is known.
+pub(crate) fn respawn(
+    transform: &mut Transform,
+    velocity: &mut Velocity,
+    respawnable: &Respawnable,
+    fall_damage: Option<Mut<FallDamage>>,
+) -> bool {
+    let Some(position) = respawnable.last_grounded_position else {
+        return false;
+    };
+    transform.translation = position;
+    *velocity = Velocity::zero();
+    // Whatever speed was reached during the fall should not hurt after the teleport
+    if let Some(mut fall_damage) = fall_damage {
+        fall_damage.peak_fall_speed = 0.0;
+    }
+    true
+}
@@ -1,11 +1,13 @@
 use std::f32::consts::E;
 
+use crate::combat::hazards::{respawn, FallDamage, Respawnable};
 use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
 use crate::level_instantiation::spawning::objects::GameCollisionGroup;
 use crate::particles::{ParticleEffects, TimedParticle};
 use crate::player_control::{camera::IngameCamera, player_embodiment::Player};
 use crate::shader::Materials;
-use crate::GameState;
+use crate::movement::general_movement::GeneralMovementSystemSet;
+use crate::simulation::SimulationSet;
 use anyhow::{Context, Result};
 use bevy::ecs::query;
 use bevy::{prelude::*, reflect::TypeUuid};
@@ -15,8 +17,15 @@ use bevy_rapier3d::prelude::*;
 use serde::{Deserialize, Serialize};
 
 pub(crate) fn health_plugin(app: &mut App) {
-    app.register_type::<Health>().add_system(apply_death); //.in_set(HealthSystemSet)
-                                                           //.in_set(OnUpdate(GameState::Playing));
+    app.register_type::<Health>()
+        .register_type::<Invulnerable>()
+        .add_systems(
+            (tick_invulnerability, apply_death)
+                .in_set(HealthSystemSet)
+                .after(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
@@ -38,12 +47,74 @@ impl Default for Health {
     }
 }
 
-fn apply_death(query_health: Query<(Entity, &Health)>, mut commands: Commands) {
-    for (entity, health) in &query_health {
-        if health.hit_points <= 0.0 {
+impl Health {
+    /// Every source of damage goes through here, so that [`Invulnerable`] characters are always spared.
+    pub(crate) fn take_damage(&mut self, amount: f32, invulnerable: Option<&Invulnerable>) {
+        if invulnerable.is_none() {
+            self.hit_points -= amount;
+        }
+    }
+
+    /// Takes all remaining hit points at once, e.g. for instant kills
+    pub(crate) fn kill(&mut self, invulnerable: Option<&Invulnerable>) {
+        let hit_points = self.hit_points.max(0.0);
+        self.take_damage(hit_points, invulnerable);
+    }
+}
+
+/// While present, [`Health::take_damage`] does nothing, e.g. during the i-frames of a dash.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Invulnerable {
+    pub(crate) remaining_seconds: f32,
+}
+
+fn tick_invulnerability(
+    fixed_time: Res<FixedTime>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in &mut query {
+        invulnerable.remaining_seconds -= fixed_time.period.as_secs_f32();
+        if invulnerable.remaining_seconds <= 0.0 {
             if let Some(mut entity_commands) = commands.get_entity(entity) {
-                entity_commands.despawn();
+                entity_commands.remove::<Invulnerable>();
             }
         }
     }
 }
+
+/// Despawns characters without hit points left. [`Respawnable`] characters like the player are healed
+/// and put back where they last stood safely instead, see [`respawn`].
+/// Healing them in place could leave them dying over and over, e.g. inside a killzone,
+/// so if no safe place is known yet, they are despawned like everyone else.
+fn apply_death(
+    mut query_health: Query<(
+        Entity,
+        &mut Health,
+        Option<(
+            &Respawnable,
+            &mut Transform,
+            &mut Velocity,
+            Option<&mut FallDamage>,
+        )>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut health, respawnable) in &mut query_health {
+        if health.hit_points > 0.0 {
+            continue;
+        }
+        let respawned = respawnable.map_or(
+            false,
+            |(respawnable, mut transform, mut velocity, fall_damage)| {
+                respawn(&mut transform, &mut velocity, respawnable, fall_damage)
+            },
+        );
+        if respawned {
+            health.hit_points = health.max_hit_points;
+        } else if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.despawn();
+        }
+    }
+}
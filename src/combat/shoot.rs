@@ -1,11 +1,14 @@
 use crate::file_system_interaction::asset_loading::AudioAssets;
 use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
 use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::movement::general_movement::Crouching;
 use crate::particles::{ParticleEffects, TimedParticle};
-use crate::player_control::{camera::IngameCamera, player_embodiment::Player};
+use crate::player_control::camera::{CameraShake, IngameCamera};
+use crate::player_control::player_embodiment::Player;
 use crate::shader::Materials;
 use crate::spatial_audio::DisposableAudioEmitterBundle;
-use crate::GameState;
+use crate::simulation::{InterpolatedTransform, SimulationRng, SimulationSet};
+use crate::util::trait_extension::Vec3Ext;
 use anyhow::Result;
 use bevy::{prelude::*, reflect::TypeUuid};
 use bevy_hanabi::{ParticleEffect, ParticleEffectBundle};
@@ -15,7 +18,7 @@ use bevy_rapier3d::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use super::health::Health;
+use super::health::{Health, Invulnerable};
 
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -25,6 +28,22 @@ pub(crate) struct Shooting {
     pub(crate) shoot_delay_enabled: bool,
     pub(crate) shoot_delay_length: f32,
     pub(crate) shoot_delay_time: f32,
+    /// Maximum angle in degrees by which a shot deviates from where the camera is looking
+    pub(crate) spread_degrees: f32,
+    /// Factor applied to [`Shooting::spread_degrees`] while crouching
+    pub(crate) crouching_spread_factor: f32,
+    /// Is the weapon aimed down its sights?
+    pub(crate) aiming: bool,
+    /// Vertical field of view in radians while [`Shooting::aiming`]
+    pub(crate) aim_fov: f32,
+    /// Factor applied to [`Shooting::spread_degrees`] while [`Shooting::aiming`]
+    pub(crate) aim_spread_factor: f32,
+    /// Factor applied to the walking speed while [`Shooting::aiming`]
+    pub(crate) aim_movement_factor: f32,
+    /// Point on the ground to shoot at horizontally instead of where the camera is looking, used by top-down aiming
+    pub(crate) target: Option<Vec3>,
+    /// Enemy to bend shots towards instead of where the camera is looking, used by gamepad aim assist
+    pub(crate) assist_target: Option<Vec3>,
 }
 
 impl Default for Shooting {
@@ -34,6 +53,30 @@ impl Default for Shooting {
             shoot_delay_enabled: false,
             shoot_delay_length: 0.0,
             shoot_delay_time: 0.0,
+            spread_degrees: 1.5,
+            crouching_spread_factor: 0.4,
+            aiming: false,
+            aim_fov: 0.45,
+            aim_spread_factor: 0.3,
+            aim_movement_factor: 0.5,
+            target: None,
+            assist_target: None,
+        }
+    }
+}
+
+impl Shooting {
+    pub(crate) fn get_spread(&self, crouching: Option<&Crouching>) -> f32 {
+        let is_crouching = crouching.map_or(false, |crouching| crouching.active);
+        let spread = if is_crouching {
+            self.spread_degrees * self.crouching_spread_factor
+        } else {
+            self.spread_degrees
+        };
+        if self.aiming {
+            spread * self.aim_spread_factor
+        } else {
+            spread
         }
     }
 }
@@ -51,39 +94,44 @@ impl Default for PhysicsProjectile {
 pub(crate) fn shooting_plugin(app: &mut App) {
     app.register_type::<Shooting>()
         .add_systems(
-            (apply_shooting, apply_projectile_impact)
+            (apply_shooting, handle_tracing_projectile_movement, apply_projectile_impact)
                 .chain()
                 .in_set(ShootingSystemSet)
-                .in_set(OnUpdate(GameState::Playing)),
-        )
-        .add_system(handle_tracing_projectile_movement);
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub(crate) struct ShootingSystemSet;
 
+/// Added to the [`CameraShake`] by every shot
+const FIRING_TRAUMA: f32 = 0.12;
+
 #[sysfail(log(level = "error"))]
 fn apply_shooting(
     //mut player_query: Query<(&mut Shooting, &Transform, &mut CustomAudioEmitter), With<Player>>,
-    mut player_query: Query<(&mut Shooting, &Transform), With<Player>>,
+    mut player_query: Query<(&mut Shooting, &Transform, Option<&Crouching>), With<Player>>,
     camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     materials: Res<Materials>,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     //mut audio_instances: ResMut<Assets<AudioInstance>>,
     audio_assets: Res<AudioAssets>,
     audio: Res<Audio>,
+    mut rng: ResMut<SimulationRng>,
+    mut camera_shake: ResMut<CameraShake>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("handle_horizontal_movement").entered();
     let Some((_camera, camera_transform)) = camera_query.iter().next() else {
         return Ok(());
     };
-    let dt = time.delta_seconds();
+    let dt = fixed_time.period.as_secs_f32();
 
     //for (mut shooting, player_transform, mut emitter) in &mut player_query {
-    for (mut shooting, player_transform) in &mut player_query {
+    for (mut shooting, player_transform, crouching) in &mut player_query {
         if shooting.shoot_delay_enabled {
             if shooting.shoot_delay_time >= shooting.shoot_delay_length {
                 shooting.shoot_delay_enabled = false;
@@ -94,14 +142,39 @@ fn apply_shooting(
         }
 
         if shooting.requested && !shooting.shoot_delay_enabled {
-            let forward = camera_transform.forward();
             const SPAWN_FORWARD_ADJUST: f32 = 2.0;
+            const TOP_DOWN_SPAWN_FORWARD_ADJUST: f32 = 0.8;
+            let (aim_transform, spawn_forward_adjust) = if let Some(target) = shooting.target {
+                let up = player_transform.up();
+                let direction = (target - player_transform.translation)
+                    .split(up)
+                    .horizontal
+                    .try_normalize()
+                    .unwrap_or_else(|| player_transform.forward());
+                (
+                    Transform::from_translation(player_transform.translation)
+                        .looking_to(direction, up),
+                    TOP_DOWN_SPAWN_FORWARD_ADJUST,
+                )
+            } else if let Some(assist_target) = shooting.assist_target {
+                (
+                    camera_transform.looking_at(assist_target, camera_transform.up()),
+                    SPAWN_FORWARD_ADJUST,
+                )
+            } else {
+                (*camera_transform, SPAWN_FORWARD_ADJUST)
+            };
+            let forward = get_spread_direction(
+                &aim_transform,
+                shooting.get_spread(crouching).to_radians(),
+                &mut *rng,
+            );
             let projectile_starting_vel = 10.0;
 
             let mesh_handle = get_or_add_mesh_handle(&mut meshes);
 
-            let projectile_transform = camera_transform
-                .with_translation((forward * SPAWN_FORWARD_ADJUST) + camera_transform.translation);
+            let projectile_transform = aim_transform
+                .with_translation((forward * spawn_forward_adjust) + aim_transform.translation);
 
             let is_physics_projectile = false;
 
@@ -139,6 +212,7 @@ fn apply_shooting(
                         },
                         Name::new("Projectile"),
                         TracingProjectile { velocity },
+                        InterpolatedTransform::from(projectile_transform),
                     ))
                     .id()
             };
@@ -165,7 +239,7 @@ fn apply_shooting(
             }*/
 
             //pick one of the shot sounds at random to provide some variety
-            let rand_shot_index = rand::thread_rng().gen_range(0..4);
+            let rand_shot_index = rng.gen_range(0..4);
             let rifle_shot_handle = match rand_shot_index {
                 1 => &audio_assets.rifle_shot_2,
                 2 => &audio_assets.rifle_shot_4,
@@ -183,6 +257,7 @@ fn apply_shooting(
                 TransformBundle::from_transform(projectile_transform),
             ));
 
+            camera_shake.add_trauma(FIRING_TRAUMA);
             shooting.requested = false;
             shooting.shoot_delay_enabled = true;
         } else if shooting.requested {
@@ -192,6 +267,17 @@ fn apply_shooting(
     Ok(())
 }
 
+fn get_spread_direction(transform: &Transform, spread: f32, rng: &mut impl Rng) -> Vec3 {
+    if spread <= 0.0 {
+        return transform.forward();
+    }
+    let yaw = rng.gen_range(-spread..=spread);
+    let pitch = rng.gen_range(-spread..=spread);
+    Quat::from_axis_angle(transform.up(), yaw)
+        * Quat::from_axis_angle(transform.right(), pitch)
+        * transform.forward()
+}
+
 fn apply_projectile_impact(
     mut collision_events: EventReader<CollisionEvent>,
     projectile_query: Query<Entity, With<PhysicsProjectile>>,
@@ -346,14 +432,14 @@ impl Default for TracingProjectile {
 fn handle_tracing_projectile_movement(
     mut tracing_projectiles: Query<(Entity, &mut TracingProjectile, &mut Transform)>,
     rapier_context: Res<RapierContext>,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     query_name: Query<&Name>,
-    mut query_health: Query<&mut Health>,
+    mut query_health: Query<(&mut Health, Option<&Invulnerable>)>,
     mut commands: Commands,
     particle_effects: Res<ParticleEffects>,
 ) {
     for (projectile_entity, mut projectile, mut transform) in tracing_projectiles.iter_mut() {
-        let dt = time.delta_seconds();
+        let dt = fixed_time.period.as_secs_f32();
         let ray_start = transform.translation;
         let travel_distance = projectile.velocity * dt;
         let gravity = Vec3::Y * -1.0; //ideally we'd pull the gravity from rapier
@@ -387,8 +473,8 @@ fn handle_tracing_projectile_movement(
                 ));
             };
 
-            if let Ok(mut health) = query_health.get_mut(entity) {
-                health.hit_points = 0.0; //all projectiles are instant kill for now
+            if let Ok((mut health, invulnerable)) = query_health.get_mut(entity) {
+                health.kill(invulnerable); //all projectiles are instant kill for now
             }
 
             projectile.velocity = Vec3::ZERO;
@@ -1,5 +1,8 @@
 use crate::file_system_interaction::game_state_serialization::{GameLoadRequest, GameSaveRequest};
 use crate::file_system_interaction::level_serialization::{WorldLoadRequest, WorldSaveRequest};
+use crate::file_system_interaction::replay::{
+    ReplayPlayRequest, ReplayRecordRequest, ReplayStopRequest,
+};
 use crate::level_instantiation::spawning::GameObject;
 use crate::player_control::camera::ForceCursorGrabMode;
 use crate::GameState;
@@ -97,6 +100,30 @@ impl EditorWindow for DevEditorWindow {
             }
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Replay name: ");
+            ui.text_edit_singleline(&mut state.replay_name);
+        });
+
+        ui.horizontal(|ui| {
+            let filename = (!state.replay_name.is_empty()).then(|| state.replay_name.clone());
+            if ui.button("Record").clicked() {
+                world.send_event(ReplayRecordRequest {
+                    filename: filename.clone(),
+                })
+            }
+            if ui.button("Stop").clicked() {
+                world.send_event(ReplayStopRequest);
+            }
+            ui.add_enabled_ui(filename.is_some(), |ui| {
+                if ui.button("Play").clicked() {
+                    world.send_event(ReplayPlayRequest {
+                        filename: state.replay_name.clone(),
+                    });
+                }
+            });
+        });
+
         ui.add_space(10.);
         ui.label("Spawning");
         if ui.button("Spawn").clicked() {
@@ -126,6 +153,7 @@ pub(crate) struct DevEditorState {
     pub(crate) open: bool,
     pub(crate) level_name: String,
     pub(crate) save_name: String,
+    pub(crate) replay_name: String,
     pub(crate) spawn_item: GameObject,
     pub(crate) collider_render_enabled: bool,
     pub(crate) navmesh_render_enabled: bool,
@@ -136,6 +164,7 @@ impl Default for DevEditorState {
         Self {
             level_name: "old_town".to_owned(),
             save_name: default(),
+            replay_name: default(),
             spawn_item: default(),
             collider_render_enabled: false,
             navmesh_render_enabled: false,
@@ -65,11 +65,3 @@ fn init_audio(
         walking: walking_handle,
     }); */
 }
-
-pub(crate) fn create_walking_audio_handle(source_handle: Handle<AudioSource>, audio: &Res<Audio>) -> Handle<AudioInstance> {
-    return audio
-    .play(source_handle)
-    .looped()
-    .with_volume(0.8)
-    .handle();
-}
\ No newline at end of file
@@ -20,7 +20,9 @@ pub(crate) struct GameConfig {
     pub(crate) camera: Camera,
     pub(crate) characters: Characters,
     pub(crate) player: Player,
+    pub(crate) movement: Movement,
     pub(crate) dialog: Dialog,
+    pub(crate) steering: Steering,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -31,6 +33,49 @@ pub(crate) struct Camera {
     pub(crate) third_person: ThirdPerson,
     pub(crate) mouse_sensitivity_x: f32,
     pub(crate) mouse_sensitivity_y: f32,
+    pub(crate) wall_run_tilt_degrees: f32,
+    pub(crate) wall_run_tilt_smoothing: f32,
+    pub(crate) shake: Shake,
+    pub(crate) aim_assist: AimAssist,
+}
+
+/// Tuning of the [`CameraShake`](crate::player_control::camera::CameraShake)
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct Shake {
+    /// Turn off to never shake the camera, e.g. for players prone to motion sickness
+    pub(crate) enabled: bool,
+    /// Trauma lost per second
+    pub(crate) trauma_decay: f32,
+    /// How often the shake changes direction per second
+    pub(crate) frequency: f32,
+    pub(crate) max_yaw_degrees: f32,
+    pub(crate) max_pitch_degrees: f32,
+    pub(crate) max_roll_degrees: f32,
+    /// Maximum translation in m
+    pub(crate) max_offset: f32,
+}
+
+/// Helps aiming with a gamepad in first and third person. Never applies while the camera is controlled with the mouse.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct AimAssist {
+    /// Turn the camera slower while the center of the screen is over an enemy
+    pub(crate) slowdown_enabled: bool,
+    /// Pull the camera towards the nearest enemy while turning
+    pub(crate) magnetism_enabled: bool,
+    /// Bend shots towards the nearest enemy
+    pub(crate) bullet_bending_enabled: bool,
+    /// Distance in m up to which enemies are assisted with
+    pub(crate) max_distance: f32,
+    /// Factor applied to the camera sensitivity while over an enemy
+    pub(crate) slowdown_factor: f32,
+    /// Maximum angle in degrees between the center of the screen and an enemy that pulls the camera
+    pub(crate) magnetism_angle_degrees: f32,
+    /// How quickly the camera is pulled towards the enemy. Higher is stronger.
+    pub(crate) magnetism_strength: f32,
+    /// Maximum angle in degrees by which a shot is bent towards an enemy
+    pub(crate) bullet_bending_angle_degrees: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -70,6 +115,8 @@ pub(crate) struct ThirdPerson {
     pub(crate) tracking_smoothing: f32,
     pub(crate) zoom_in_smoothing: f32,
     pub(crate) zoom_out_smoothing: f32,
+    /// Sideways distance in m of the camera from the player while aiming down sights
+    pub(crate) aim_shoulder_offset: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -87,6 +134,28 @@ pub(crate) struct Player {
     pub(crate) fov_saturation_speed: f32,
     pub(crate) min_fov: f32,
     pub(crate) max_fov: f32,
+    pub(crate) aim_zoom_smoothing: f32,
+}
+
+/// Movement tuning per kind of character. Applied to the [`Walking`](crate::movement::general_movement::Walking)
+/// and [`Jumping`](crate::movement::general_movement::Jumping) components of every character when it spawns and whenever the config is reloaded.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct Movement {
+    pub(crate) player: CharacterMovement,
+    pub(crate) npc: CharacterMovement,
+    pub(crate) enemy: CharacterMovement,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct CharacterMovement {
+    pub(crate) ground_acceleration: f32,
+    pub(crate) sprinting_acceleration: f32,
+    pub(crate) aerial_acceleration: f32,
+    pub(crate) braking_acceleration: f32,
+    pub(crate) stopping_speed: f32,
+    pub(crate) jump_speed: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -94,3 +163,15 @@ pub(crate) struct Player {
 pub(crate) struct Dialog {
     pub(crate) base_letters_per_second: f32,
 }
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct Steering {
+    pub(crate) neighbour_radius: f32,
+    pub(crate) separation_distance: f32,
+    pub(crate) separation_weight: f32,
+    pub(crate) obstacle_lookahead: f32,
+    pub(crate) obstacle_weight: f32,
+    pub(crate) avoidance_time_horizon: f32,
+    pub(crate) avoidance_weight: f32,
+}
@@ -39,7 +39,7 @@ pub(crate) struct GameLoadRequest {
 }
 
 #[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize, Default)]
-struct SaveModel {
+pub(crate) struct SaveModel {
     scene: String,
     #[serde(default, skip_serializing_if = "ActiveConditions::is_empty")]
     conditions: ActiveConditions,
@@ -104,21 +104,38 @@ fn handle_load_requests(
                 continue;
             }
         };
-        loader.send(WorldLoadRequest {
-            filename: save_model.scene,
-        });
-        if let Some(dialog_event) = save_model.dialog_event {
-            dialog_event_writer.send(dialog_event);
-        }
-        commands.insert_resource(save_model.conditions);
-
-        spawner.send(
-            SpawnEvent::with_data(GameObject::Player, save_model.player_transform).delay_frames(2),
+        load_save_model(
+            &mut commands,
+            save_model,
+            &mut loader,
+            &mut spawner,
+            &mut dialog_event_writer,
         );
     }
     Ok(())
 }
 
+/// Loads the level of the save and respawns the player in it. The player is spawned two frames later, after the level.
+pub(crate) fn load_save_model(
+    commands: &mut Commands,
+    save_model: SaveModel,
+    loader: &mut EventWriter<WorldLoadRequest>,
+    spawner: &mut EventWriter<SpawnEvent<GameObject, Transform>>,
+    dialog_event_writer: &mut EventWriter<DialogEvent>,
+) {
+    loader.send(WorldLoadRequest {
+        filename: save_model.scene,
+    });
+    if let Some(dialog_event) = save_model.dialog_event {
+        dialog_event_writer.send(dialog_event);
+    }
+    commands.insert_resource(save_model.conditions);
+
+    spawner.send(
+        SpawnEvent::with_data(GameObject::Player, save_model.player_transform).delay_frames(2),
+    );
+}
+
 #[sysfail(log(level = "error"))]
 fn handle_save_requests(
     mut save_events: EventReader<GameSaveRequest>,
@@ -127,20 +144,10 @@ fn handle_save_requests(
     player_query: Query<&GlobalTransform, With<Player>>,
     current_level: Res<CurrentLevel>,
 ) -> Result<()> {
-    let dialog = dialog.map(|dialog| dialog.clone());
     for save in save_events.iter() {
         for player in &player_query {
-            let dialog_event = dialog.clone().map(|dialog| DialogEvent {
-                dialog: dialog.id,
-                source: dialog.source,
-                page: Some(dialog.current_page),
-            });
-            let save_model = SaveModel {
-                scene: current_level.scene.clone(),
-                conditions: conditions.clone(),
-                dialog_event,
-                player_transform: player.compute_transform(),
-            };
+            let save_model =
+                create_save_model(&conditions, dialog.as_deref(), player, &current_level);
             let serialized = match ron::to_string(&save_model) {
                 Ok(string) => string,
                 Err(e) => {
@@ -164,6 +171,25 @@ fn handle_save_requests(
     Ok(())
 }
 
+pub(crate) fn create_save_model(
+    conditions: &ActiveConditions,
+    dialog: Option<&CurrentDialog>,
+    player: &GlobalTransform,
+    current_level: &CurrentLevel,
+) -> SaveModel {
+    let dialog_event = dialog.map(|dialog| DialogEvent {
+        dialog: dialog.id.clone(),
+        source: dialog.source,
+        page: Some(dialog.current_page.clone()),
+    });
+    SaveModel {
+        scene: current_level.scene.clone(),
+        conditions: conditions.clone(),
+        dialog_event,
+        player_transform: player.compute_transform(),
+    }
+}
+
 fn get_save_path(filename: impl Into<Cow<'static, str>>) -> PathBuf {
     let filename = filename.into().to_string();
     Path::new("saves").join(filename).with_extension("sav.ron")
@@ -0,0 +1,526 @@
+use crate::file_system_interaction::game_state_serialization::{
+    create_save_model, load_save_model, SaveModel,
+};
+use crate::file_system_interaction::level_serialization::{CurrentLevel, WorldLoadRequest};
+use crate::level_instantiation::spawning::GameObject;
+use crate::player_control::actions::{CameraAction, PlayerAction};
+use crate::player_control::aim_assist::ActiveInputDevice;
+use crate::player_control::camera::IngameCamera;
+use crate::player_control::controls::Controls;
+use crate::player_control::player_embodiment::{LatchedActions, Player};
+use crate::simulation::{
+    is_simulation_running, SimulationFrozen, SimulationRng, SimulationSet, SimulationTick,
+};
+use crate::world_interaction::condition::ActiveConditions;
+use crate::world_interaction::dialog::{CurrentDialog, DialogEvent};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use spew::prelude::*;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Records the player's input into a replay file and plays it back deterministically, e.g. for bug reports or as regression tests
+/// for movement and combat. Recording reloads the game from a save of the current state, so that the recording and every playback
+/// start from exactly the same freshly loaded level. The simulation is frozen while the level loads and starts with the [`SimulationRng`]
+/// reseeded on the first tick afterwards, so how many frames loading takes does not matter.
+/// The input is stored per simulation tick instead of per frame, so a replay plays out the same regardless of the frame rate.
+/// During playback, the live player and camera input is ignored.
+/// Starting the game with `--check-replay <filename>` plays back a replay without showing a window, see [`ReplayCheck`].
+pub(crate) fn replay_plugin(app: &mut App) {
+    if let Some(replay_check) = ReplayCheck::from_args() {
+        app.insert_resource(replay_check);
+    }
+    app.add_event::<ReplayRecordRequest>()
+        .add_event::<ReplayPlayRequest>()
+        .add_event::<ReplayStopRequest>()
+        .add_event::<ReplayFinished>()
+        .add_systems(
+            (
+                start_recording.run_if(resource_exists::<CurrentLevel>()),
+                start_playback,
+                stop_replay,
+                exit_after_replay_check.run_if(resource_exists::<ReplayCheck>()),
+                unfreeze_when_loaded,
+                ignore_live_input.run_if(resource_exists::<ReplayPlayback>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            skip_menu_for_replay_check
+                .run_if(resource_exists::<ReplayCheck>())
+                .in_set(OnUpdate(GameState::Menu)),
+        )
+        .add_system(
+            start_replay_check
+                .run_if(resource_exists::<ReplayCheck>())
+                .in_schedule(OnEnter(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                begin_on_first_tick,
+                record_tick.run_if(resource_exists::<ReplayRecording>()),
+                play_tick.run_if(resource_exists::<ReplayPlayback>()),
+            )
+                .chain()
+                .after(SimulationSet::Restore)
+                .before(SimulationSet::Input)
+                .distributive_run_if(in_state(GameState::Playing))
+                .distributive_run_if(is_simulation_running)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// Starts recording a replay that is written to `replays/<filename>.replay.ron` when a [`ReplayStopRequest`] arrives.
+/// Without a filename, the current date and time is used.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub(crate) struct ReplayRecordRequest {
+    pub(crate) filename: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub(crate) struct ReplayPlayRequest {
+    pub(crate) filename: String,
+}
+
+/// Ends the current recording or playback
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub(crate) struct ReplayStopRequest;
+
+/// Sent when a playback ran through all ticks of its replay
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub(crate) struct ReplayFinished {
+    pub(crate) filename: String,
+    pub(crate) diverged_at: Option<usize>,
+}
+
+/// Present when the game was started with `--check-replay <filename>`, e.g. to run replays as regression tests in CI.
+/// Skips the menu, plays back the replay in an invisible window and exits once it is over.
+/// The exit code is non-zero if the replay could not be played back or diverged from the recording.
+#[derive(Debug, Clone, Eq, PartialEq, Resource)]
+pub(crate) struct ReplayCheck {
+    pub(crate) filename: String,
+}
+
+impl ReplayCheck {
+    pub(crate) fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip_while(|arg| arg != "--check-replay");
+        args.next()?;
+        args.next().map(|filename| Self { filename })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Replay {
+    seed: u64,
+    start: SaveModel,
+    ticks: Vec<ReplayTick>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ReplayTick {
+    player_actions: ActionState<PlayerAction>,
+    /// Presses are latched per frame, so how they fall onto ticks depends on the frame rate
+    latched_actions: LatchedActions,
+    camera_actions: ActionState<CameraAction>,
+    /// Movement is relative to the camera, which is moved every frame rather than every tick, so it needs to be recorded as well
+    camera_transform: Transform,
+    /// Decides whether shots are bent towards enemies
+    active_input_device: ActiveInputDevice,
+    /// Where the player was at the start of the tick, to find out when a playback diverges from the recording
+    player_translation: Vec3,
+}
+
+/// Present while recording
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub(crate) struct ReplayRecording {
+    filename: String,
+    replay: Replay,
+    /// Whether the first tick after the reloaded level finished loading has happened, which is when recording starts
+    started: bool,
+}
+
+/// Present while playing back
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub(crate) struct ReplayPlayback {
+    filename: String,
+    replay: Replay,
+    /// Whether the first tick after the level finished loading has happened, which is when playback starts
+    started: bool,
+    next_tick: usize,
+    /// The first tick where the player was somewhere else than in the recording
+    diverged_at: Option<usize>,
+}
+
+/// Distance in m the player may be off from the recording before the playback counts as diverged
+const DIVERGENCE_TOLERANCE: f32 = 1e-3;
+
+fn start_recording(
+    mut commands: Commands,
+    mut record_requests: EventReader<ReplayRecordRequest>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    conditions: Res<ActiveConditions>,
+    dialog: Option<Res<CurrentDialog>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    current_level: Res<CurrentLevel>,
+    mut loader: EventWriter<WorldLoadRequest>,
+    mut spawner: EventWriter<SpawnEvent<GameObject, Transform>>,
+    mut dialog_event_writer: EventWriter<DialogEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_recording").entered();
+    let Some(request) = record_requests.iter().last() else {
+        return;
+    };
+    if replay_playback.is_some() {
+        error!("Failed to start recording a replay: A replay is currently being played back");
+        return;
+    }
+    let Some(player) = player_query.iter().next() else {
+        error!("Failed to start recording a replay: No player found");
+        return;
+    };
+    let save_model = create_save_model(&conditions, dialog.as_deref(), player, &current_level);
+    let filename = request
+        .filename
+        .clone()
+        .unwrap_or_else(|| chrono::Local::now().to_rfc2822().replace(':', "-"));
+    commands.insert_resource(ReplayRecording {
+        filename,
+        replay: Replay {
+            seed: rand::random(),
+            start: save_model.clone(),
+            ticks: default(),
+        },
+        started: false,
+    });
+    commands.insert_resource(SimulationFrozen);
+    load_save_model(
+        &mut commands,
+        save_model,
+        &mut loader,
+        &mut spawner,
+        &mut dialog_event_writer,
+    );
+    info!("Started recording a replay");
+}
+
+#[sysfail(log(level = "error"))]
+fn start_playback(
+    mut commands: Commands,
+    mut play_requests: EventReader<ReplayPlayRequest>,
+    replay_recording: Option<Res<ReplayRecording>>,
+    mut loader: EventWriter<WorldLoadRequest>,
+    mut spawner: EventWriter<SpawnEvent<GameObject, Transform>>,
+    mut dialog_event_writer: EventWriter<DialogEvent>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_playback").entered();
+    let Some(request) = play_requests.iter().last() else {
+        return Ok(());
+    };
+    if replay_recording.is_some() {
+        error!("Failed to play back a replay: A replay is currently being recorded");
+        return Ok(());
+    }
+    let replay = read_replay(request.filename.clone())?;
+    load_save_model(
+        &mut commands,
+        replay.start.clone(),
+        &mut loader,
+        &mut spawner,
+        &mut dialog_event_writer,
+    );
+    commands.insert_resource(SimulationFrozen);
+    commands.insert_resource(ReplayPlayback {
+        filename: request.filename.clone(),
+        replay,
+        started: false,
+        next_tick: 0,
+        diverged_at: None,
+    });
+    info!("Started playing back replay {}", request.filename);
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn stop_replay(
+    mut commands: Commands,
+    mut stop_requests: EventReader<ReplayStopRequest>,
+    replay_recording: Option<Res<ReplayRecording>>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    mut replay_finished: EventWriter<ReplayFinished>,
+    controls: Res<Controls>,
+    player_query: Query<
+        Entity,
+        (
+            With<ActionState<PlayerAction>>,
+            Without<InputMap<PlayerAction>>,
+        ),
+    >,
+    camera_query: Query<
+        Entity,
+        (
+            With<ActionState<CameraAction>>,
+            Without<InputMap<CameraAction>>,
+        ),
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("stop_replay").entered();
+    let is_playback_over = replay_playback.as_ref().map_or(false, |playback| {
+        playback.next_tick >= playback.replay.ticks.len()
+    });
+    if stop_requests.iter().count() == 0 && !is_playback_over {
+        return Ok(());
+    }
+    commands.remove_resource::<SimulationFrozen>();
+    if let Some(recording) = replay_recording {
+        commands.remove_resource::<ReplayRecording>();
+        let serialized = ron::to_string(&recording.replay).context("Failed to serialize replay")?;
+        let path = get_replay_path(recording.filename.clone());
+        let dir = path.parent().context("Failed to get replay directory")?;
+        fs::create_dir_all(dir).context("Failed to create replay directory")?;
+        fs::write(&path, serialized)
+            .with_context(|| format!("Failed to write replay {}", recording.filename))?;
+        info!(
+            "Successfully recorded {} ticks into replay at {}",
+            recording.replay.ticks.len(),
+            path.to_string_lossy()
+        );
+    }
+    if let Some(playback) = replay_playback {
+        commands.remove_resource::<ReplayPlayback>();
+        // Give the controls back to the player
+        for entity in &player_query {
+            commands.entity(entity).insert(controls.player.clone());
+        }
+        for entity in &camera_query {
+            commands.entity(entity).insert(controls.camera.clone());
+        }
+        if is_playback_over {
+            replay_finished.send(ReplayFinished {
+                filename: playback.filename.clone(),
+                diverged_at: playback.diverged_at,
+            });
+        }
+        if let Some(tick) = playback.diverged_at {
+            warn!(
+                "Replay {} diverged from the recording at tick {tick}",
+                playback.filename
+            );
+        } else if is_playback_over {
+            info!(
+                "Replay {} matched the recording for all {} ticks",
+                playback.filename,
+                playback.replay.ticks.len()
+            );
+        } else {
+            info!("Stopped replay {}", playback.filename);
+        }
+    }
+    Ok(())
+}
+
+fn skip_menu_for_replay_check(mut next_state: ResMut<NextState<GameState>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("skip_menu_for_replay_check").entered();
+    next_state.set(GameState::Playing);
+}
+
+fn start_replay_check(
+    replay_check: Res<ReplayCheck>,
+    mut play_requests: EventWriter<ReplayPlayRequest>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_replay_check").entered();
+    // The playback itself only logs errors, which would leave the check hanging
+    if let Err(e) = read_replay(replay_check.filename.clone()) {
+        error!("Replay check failed: {e:#}");
+        std::process::exit(1);
+    }
+    play_requests.send(ReplayPlayRequest {
+        filename: replay_check.filename.clone(),
+    });
+}
+
+fn exit_after_replay_check(
+    mut replay_finished: EventReader<ReplayFinished>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("exit_after_replay_check").entered();
+    for finished in replay_finished.iter() {
+        if let Some(tick) = finished.diverged_at {
+            error!(
+                "Replay check failed: {} diverged from the recording at tick {tick}",
+                finished.filename
+            );
+            std::process::exit(1);
+        }
+        info!("Replay check passed: {}", finished.filename);
+        app_exit_events.send(AppExit);
+    }
+}
+
+/// The level has finished loading once the player of the freshly loaded level has spawned and no more colliders are being added
+fn unfreeze_when_loaded(
+    mut commands: Commands,
+    added_player: Query<(), Added<Player>>,
+    added_colliders: Query<(), Added<Collider>>,
+    replay_recording: Option<Res<ReplayRecording>>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    mut is_player_spawned: Local<bool>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("unfreeze_when_loaded").entered();
+    let is_loading = replay_recording.map_or(false, |recording| !recording.started)
+        || replay_playback.map_or(false, |playback| !playback.started);
+    if !is_loading {
+        *is_player_spawned = false;
+        return;
+    }
+    *is_player_spawned |= !added_player.is_empty();
+    if *is_player_spawned && added_colliders.is_empty() {
+        commands.remove_resource::<SimulationFrozen>();
+    }
+}
+
+/// Starts the recording or playback on the first tick after loading, so that it starts from the same state every time
+fn begin_on_first_tick(
+    replay_recording: Option<ResMut<ReplayRecording>>,
+    replay_playback: Option<ResMut<ReplayPlayback>>,
+    mut rng: ResMut<SimulationRng>,
+    mut tick: ResMut<SimulationTick>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("begin_on_first_tick").entered();
+    let seed = if let Some(mut recording) = replay_recording && !recording.started {
+        recording.started = true;
+        recording.replay.seed
+    } else if let Some(mut playback) = replay_playback && !playback.started {
+        playback.started = true;
+        playback.replay.seed
+    } else {
+        return;
+    };
+    rng.reseed(seed);
+    tick.0 = 0;
+}
+
+fn ignore_live_input(
+    mut commands: Commands,
+    player_query: Query<Entity, With<InputMap<PlayerAction>>>,
+    camera_query: Query<Entity, With<InputMap<CameraAction>>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("ignore_live_input").entered();
+    // Without an input map, the input manager leaves the action state alone
+    for entity in player_query.iter().chain(camera_query.iter()) {
+        commands
+            .entity(entity)
+            .remove::<InputMap<PlayerAction>>()
+            .remove::<InputMap<CameraAction>>();
+    }
+}
+
+fn record_tick(
+    mut recording: ResMut<ReplayRecording>,
+    player_query: Query<
+        (&ActionState<PlayerAction>, &LatchedActions, &Transform),
+        With<Player>,
+    >,
+    camera_query: Query<(&ActionState<CameraAction>, &Transform), With<IngameCamera>>,
+    active_input_device: Res<ActiveInputDevice>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("record_tick").entered();
+    if !recording.started {
+        return;
+    }
+    let Some((player_actions, latched_actions, player_transform)) = player_query.iter().next()
+    else {
+        return;
+    };
+    let Some((camera_actions, camera_transform)) = camera_query.iter().next() else {
+        return;
+    };
+    recording.replay.ticks.push(ReplayTick {
+        player_actions: player_actions.clone(),
+        latched_actions: *latched_actions,
+        camera_actions: camera_actions.clone(),
+        camera_transform: *camera_transform,
+        active_input_device: *active_input_device,
+        player_translation: player_transform.translation,
+    });
+}
+
+fn play_tick(
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_query: Query<
+        (
+            &mut ActionState<PlayerAction>,
+            &mut LatchedActions,
+            &Transform,
+        ),
+        With<Player>,
+    >,
+    mut camera_query: Query<
+        (&mut ActionState<CameraAction>, &mut Transform),
+        (With<IngameCamera>, Without<Player>),
+    >,
+    mut active_input_device: ResMut<ActiveInputDevice>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_tick").entered();
+    if !playback.started {
+        return;
+    }
+    let index = playback.next_tick;
+    let Some(tick) = playback.replay.ticks.get(index).cloned() else {
+        return;
+    };
+    let Some((mut player_actions, mut latched_actions, player_transform)) =
+        player_query.iter_mut().next()
+    else {
+        return;
+    };
+    let Some((mut camera_actions, mut camera_transform)) = camera_query.iter_mut().next() else {
+        return;
+    };
+    *player_actions = tick.player_actions;
+    *latched_actions = tick.latched_actions;
+    *camera_actions = tick.camera_actions;
+    *camera_transform = tick.camera_transform;
+    *active_input_device = tick.active_input_device;
+    let is_diverged = player_transform
+        .translation
+        .distance(tick.player_translation)
+        > DIVERGENCE_TOLERANCE;
+    if is_diverged && playback.diverged_at.is_none() {
+        playback.diverged_at = Some(index);
+    }
+    playback.next_tick += 1;
+}
+
+fn read_replay(filename: impl Into<Cow<'static, str>>) -> Result<Replay> {
+    let path = get_replay_path(filename);
+    let serialized = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read replay at {}", path.to_string_lossy()))?;
+    ron::from_str(&serialized)
+        .with_context(|| format!("Failed to deserialize replay at {}", path.to_string_lossy()))
+}
+
+fn get_replay_path(filename: impl Into<Cow<'static, str>>) -> PathBuf {
+    let filename = filename.into().to_string();
+    Path::new("replays")
+        .join(filename)
+        .with_extension("replay.ron")
+}
@@ -0,0 +1,239 @@
+use crate::file_system_interaction::asset_loading::AudioAssets;
+use crate::movement::general_movement::{Crouching, Grounded};
+use crate::spatial_audio::{AudioEmitterHandle, DisposableAudioEmitterBundle};
+use crate::util::trait_extension::Vec3Ext;
+use crate::GameState;
+use anyhow::{bail, Result};
+use bevy::hierarchy::HierarchyQueryExt;
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioSource, AudioTween};
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Plays a footstep sound every time a character with [`Footsteps`] has walked a stride.
+/// The sound depends on the [`Surface`] under the character's feet and is emitted through spatial audio,
+/// so that the player can hear enemies approach.
+pub(crate) fn footstep_plugin(app: &mut App) {
+    app.register_type::<Surface>()
+        .register_type::<Footsteps>()
+        .register_type::<FootstepSound>()
+        .add_systems(
+            (read_surfaces, play_footsteps, cut_footstep_sounds)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+/// What the ground is made of, authored in the level by tagging a `[collider]` object with e.g. `[surface:gravel]`.
+/// Untagged colliders count as [`Surface::Default`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Component,
+    Reflect,
+    FromReflect,
+    Serialize,
+    Deserialize,
+    Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) enum Surface {
+    #[default]
+    Default,
+    Gravel,
+    Grass,
+    Wood,
+    Metal,
+    Stone,
+}
+
+impl Surface {
+    fn from_tag(tag: &str) -> Result<Self> {
+        Ok(match tag {
+            "default" => Surface::Default,
+            "gravel" => Surface::Gravel,
+            "grass" => Surface::Grass,
+            "wood" => Surface::Wood,
+            "metal" => Surface::Metal,
+            "stone" => Surface::Stone,
+            _ => bail!("Unknown surface \"{tag}\""),
+        })
+    }
+
+    /// Returns the sample to play along with its playback rate and volume.
+    /// Until we have dedicated samples per surface, they all share the walking sample and differ in pitch and loudness.
+    fn get_sound(self, audio_assets: &AudioAssets) -> (Handle<AudioSource>, f64, f32) {
+        let (playback_rate, volume) = match self {
+            Surface::Default => (1.0, 0.8),
+            Surface::Gravel => (0.9, 1.0),
+            Surface::Grass => (0.8, 0.5),
+            Surface::Wood => (1.15, 0.9),
+            Surface::Metal => (1.4, 1.0),
+            Surface::Stone => (1.05, 0.8),
+        };
+        (audio_assets.walking.clone(), playback_rate, volume)
+    }
+}
+
+/// Lets a character make noise when walking.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Footsteps {
+    /// Distance in m between two steps
+    pub(crate) stride_length: f32,
+    /// Seconds of the sample to play per step
+    pub(crate) step_seconds: f32,
+    pub(crate) distance_since_last_step: f32,
+}
+
+impl Default for Footsteps {
+    fn default() -> Self {
+        Self {
+            stride_length: 0.7,
+            step_seconds: 0.3,
+            distance_since_last_step: 0.0,
+        }
+    }
+}
+
+/// A single step that is still playing. Faded out after [`FootstepSound::remaining_seconds`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct FootstepSound {
+    pub(crate) remaining_seconds: f32,
+}
+
+static SURFACE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[surface:\s*(\w+)\]").expect("Failed to compile surface regex")
+});
+
+#[sysfail(log(level = "error"))]
+fn read_surfaces(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name), Added<Name>>,
+    children: Query<&Children>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_surfaces").entered();
+    for (entity, name) in &added_name {
+        let name = name.to_lowercase();
+        let Some(captures) = SURFACE_REGEX.captures(&name) else {
+            continue;
+        };
+        let surface = Surface::from_tag(&captures[1])?;
+        // The colliders are created on the meshes below the tagged object
+        for surface_entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            if let Some(mut entity_commands) = commands.get_entity(surface_entity) {
+                entity_commands.insert(surface);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn play_footsteps(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut characters: Query<(
+        Entity,
+        &mut Footsteps,
+        &Transform,
+        &Velocity,
+        &Collider,
+        &Grounded,
+        Option<&Crouching>,
+    )>,
+    surfaces: Query<&Surface>,
+    rapier_context: Res<RapierContext>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_footsteps").entered();
+    let dt = time.delta_seconds();
+    for (entity, mut footsteps, transform, velocity, collider, grounded, crouching) in
+        &mut characters
+    {
+        if !grounded.is_grounded() {
+            // Start with a fresh stride after landing
+            footsteps.distance_since_last_step = 0.0;
+            continue;
+        }
+        let horizontal_speed = grounded
+            .get_relative_velocity(velocity.linvel)
+            .split(transform.up())
+            .horizontal
+            .length();
+        footsteps.distance_since_last_step += horizontal_speed * dt;
+        if footsteps.distance_since_last_step < footsteps.stride_length {
+            continue;
+        }
+        footsteps.distance_since_last_step = 0.0;
+
+        let half_height = collider.raw.compute_local_aabb().maxs.y;
+        let feet = transform.translation - transform.up() * half_height;
+        let surface = rapier_context
+            .cast_ray(
+                transform.translation,
+                transform.down(),
+                half_height + 0.1,
+                true,
+                QueryFilter::new()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            )
+            .and_then(|(ground, _toi)| surfaces.get(ground).ok().copied())
+            .unwrap_or_default();
+        let (sample, playback_rate, volume) = surface.get_sound(&audio_assets);
+        let instance = audio
+            .play(sample)
+            .with_playback_rate(playback_rate)
+            .handle();
+        let noise_factor = crouching.map_or(1.0, |crouching| crouching.get_noise_factor());
+        let mut emitter = DisposableAudioEmitterBundle::new(
+            instance,
+            TransformBundle::from_transform(Transform::from_translation(feet)),
+        );
+        emitter.emitter_handle.volume = volume * noise_factor;
+        commands.spawn((
+            Name::new("Footstep"),
+            emitter,
+            FootstepSound {
+                remaining_seconds: footsteps.step_seconds,
+            },
+        ));
+    }
+}
+
+/// The walking sample contains several steps, so only its beginning is played per step
+fn cut_footstep_sounds(
+    time: Res<Time>,
+    mut sounds: Query<(&mut FootstepSound, &AudioEmitterHandle)>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("cut_footstep_sounds").entered();
+    for (mut sound, emitter) in &mut sounds {
+        if sound.remaining_seconds <= 0.0 {
+            continue;
+        }
+        sound.remaining_seconds -= time.delta_seconds();
+        if sound.remaining_seconds > 0.0 {
+            continue;
+        }
+        if let Some(instance) = emitter
+            .instance
+            .as_ref()
+            .and_then(|instance| audio_instances.get_mut(instance))
+        {
+            instance.stop(AudioTween::linear(Duration::from_millis(50)));
+        }
+    }
+}
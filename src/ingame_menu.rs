@@ -1,4 +1,6 @@
-use crate::player_control::actions::{ActionsFrozen, UiAction};
+use crate::player_control::actions::{InputContext, InputContextStack, UiAction};
+use crate::player_control::camera::PhotoMode;
+use crate::player_control::controls::ControlsScreen;
 use crate::GameState;
 use bevy::app::AppExit;
 use bevy::prelude::*;
@@ -7,14 +9,20 @@ use leafwing_input_manager::prelude::ActionState;
 
 /// Handles the pause menu accessed while playing the game via ESC.
 pub(crate) fn ingame_menu_plugin(app: &mut App) {
-    app.add_system(handle_pause.in_set(OnUpdate(GameState::Playing)));
+    app.add_system(
+        handle_pause
+            .run_if(not(resource_exists::<PhotoMode>()))
+            .run_if(not(resource_exists::<ControlsScreen>()))
+            .in_set(OnUpdate(GameState::Playing)),
+    );
 }
 
 fn handle_pause(
+    mut commands: Commands,
     mut time: ResMut<Time>,
     actions: Query<&ActionState<UiAction>>,
     mut app_exit_events: EventWriter<AppExit>,
-    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut input_contexts: ResMut<InputContextStack>,
     mut egui_contexts: EguiContexts,
     mut paused: Local<bool>,
 ) {
@@ -24,7 +32,7 @@ fn handle_pause(
             if toggled {
                 *paused = false;
                 time.unpause();
-                actions_frozen.unfreeze();
+                input_contexts.pop(InputContext::Menu);
             } else {
                 egui::CentralPanel::default()
                     .frame(egui::Frame {
@@ -42,6 +50,9 @@ fn handle_pause(
 
                             ui.add_space(100.0);
 
+                            if ui.button("Controls").clicked() {
+                                commands.init_resource::<ControlsScreen>();
+                            }
                             if ui.button("Quit Game").clicked() {
                                 app_exit_events.send(AppExit);
                                 #[cfg(feature = "wasm")]
@@ -53,7 +64,7 @@ fn handle_pause(
         } else if toggled {
             *paused = true;
             time.pause();
-            actions_frozen.freeze();
+            input_contexts.push(InputContext::Menu);
         }
     }
 }
@@ -1,8 +1,12 @@
+use crate::combat::hazards::FallDamage;
 use crate::combat::health::Health;
 use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets};
+use crate::footsteps::Footsteps;
 use crate::level_instantiation::spawning::GameObject;
 use crate::movement::general_movement::{CharacterAnimations, CharacterControllerBundle, Model};
-use crate::movement::navigation::Follower;
+use crate::movement::navigation::hearing::Hearing;
+use crate::movement::navigation::steering::Steering;
+use crate::movement::navigation::{Follower, FollowerPath};
 use bevy::prelude::*;
 use std::f32::consts::TAU;
 use serde::{Deserialize, Serialize};
@@ -26,15 +30,21 @@ pub(crate) fn spawn(
             Name::new("Enemy"),
             CharacterControllerBundle::capsule(HEIGHT, RADIUS),
             Follower,
+            Steering::with_radius(RADIUS),
+            FollowerPath::default(),
+            Hearing::default(),
             CharacterAnimations {
                 idle: animations.character_idle.clone(),
                 walk: animations.character_walking.clone(),
                 aerial: animations.character_running.clone(),
+                climb: animations.character_walking.clone(),
             },
             Health {
                 hit_points: 100.0,
                 max_hit_points: 100.0,
             },
+            FallDamage::default(),
+            Footsteps::default(),
             GameObject::Enemy,
             EnemyTag::default(),
         ))
@@ -97,15 +107,21 @@ pub(crate) fn spawn_enemies_on_endless_spawner(
                     Name::new("Enemy"),
                     CharacterControllerBundle::capsule(HEIGHT, RADIUS),
                     Follower,
+                    Steering::with_radius(RADIUS),
+                    FollowerPath::default(),
+                    Hearing::default(),
                     CharacterAnimations {
                         idle: animations.character_idle.clone(),
                         walk: animations.character_walking.clone(),
                         aerial: animations.character_running.clone(),
+                        climb: animations.character_walking.clone(),
                     },
                     Health {
                         hit_points: 100.0,
                         max_hit_points: 100.0,
                     },
+                    FallDamage::default(),
+                    Footsteps::default(),
                     GameObject::Enemy,
                     EnemyTag::default(),
                 ))
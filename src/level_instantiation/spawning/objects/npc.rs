@@ -2,7 +2,8 @@ use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets
 use crate::level_instantiation::spawning::objects::GameCollisionGroup;
 use crate::level_instantiation::spawning::GameObject;
 use crate::movement::general_movement::{CharacterAnimations, CharacterControllerBundle, Model};
-use crate::movement::navigation::Follower;
+use crate::movement::navigation::steering::Steering;
+use crate::movement::navigation::{Follower, FollowerPath};
 use crate::world_interaction::dialog::{DialogId, DialogTarget};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
@@ -26,10 +27,13 @@ pub(crate) fn spawn(
             Name::new("NPC"),
             CharacterControllerBundle::capsule(HEIGHT, RADIUS),
             Follower,
+            Steering::with_radius(RADIUS),
+            FollowerPath::default(),
             CharacterAnimations {
                 idle: animations.character_idle.clone(),
                 walk: animations.character_walking.clone(),
                 aerial: animations.character_running.clone(),
+                climb: animations.character_walking.clone(),
             },
             DialogTarget {
                 dialog_id: DialogId::new("follower"),
@@ -1,14 +1,21 @@
+use crate::combat::hazards::{FallDamage, Respawnable};
+use crate::combat::health::Health;
 use crate::combat::shoot::Shooting;
-use crate::file_system_interaction::asset_loading::{AnimationAssets, AudioAssets, SceneAssets};
-use crate::file_system_interaction::audio::create_walking_audio_handle;
+use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets};
+use crate::footsteps::Footsteps;
 use crate::level_instantiation::spawning::objects::GameCollisionGroup;
 use crate::level_instantiation::spawning::GameObject;
-use crate::movement::general_movement::{CharacterAnimations, CharacterControllerBundle, Model};
+use crate::movement::general_movement::{
+    CharacterAnimations, CharacterControllerBundle, Crouching, Dashing, Model,
+};
+use crate::movement::ladder::LadderClimber;
+use crate::movement::parkour::{Mantling, WallRunning};
+use crate::movement::water::Breath;
 use crate::player_control::actions::{
     create_player_action_input_manager_bundle, create_ui_action_input_manager_bundle,
 };
-use crate::player_control::player_embodiment::Player;
-use crate::spatial_audio::LoopAudioEmitterBundle;
+use crate::player_control::lock_on::LockOn;
+use crate::player_control::player_embodiment::{LatchedActions, Player};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use std::f32::consts::TAU;
@@ -21,7 +28,6 @@ pub(crate) fn spawn(
     mut commands: Commands,
     animations: Res<AnimationAssets>,
     scene_handles: Res<SceneAssets>,
-    audio_assets: Res<AudioAssets>,
 ) {
     let entity = commands
         .spawn((
@@ -33,22 +39,47 @@ pub(crate) fn spawn(
             Name::new("Player"),
             Ccd::enabled(),
             CharacterControllerBundle::capsule(HEIGHT, RADIUS),
+            // Bundle tuples are limited in size, so related components are grouped
+            (
+                Crouching::new(HEIGHT, RADIUS),
+                Dashing::default(),
+                LadderClimber::default(),
+                Breath::default(),
+                Mantling::default(),
+                WallRunning::default(),
+            ),
+            (
+                // The spawn point is safe, so the player always has somewhere to respawn
+                Respawnable {
+                    last_grounded_position: Some(transform.translation),
+                },
+                FallDamage::default(),
+                Health {
+                    hit_points: 100.0,
+                    max_hit_points: 100.0,
+                },
+            ),
             CharacterAnimations {
                 idle: animations.character_idle.clone(),
                 walk: animations.character_walking.clone(),
                 aerial: animations.character_running.clone(),
+                climb: animations.character_walking.clone(),
             },
             CollisionGroups::new(
                 GameCollisionGroup::PLAYER.into(),
                 GameCollisionGroup::ALL.into(),
             ),
-            create_player_action_input_manager_bundle(),
-            create_ui_action_input_manager_bundle(),
+            (
+                create_player_action_input_manager_bundle(),
+                create_ui_action_input_manager_bundle(),
+                LatchedActions::default(),
+            ),
             Shooting {
                 shoot_delay_length: 0.1,
                 ..default()
             },
-            LoopAudioEmitterBundle::new(audio_assets.walking.clone(), create_walking_audio_handle),
+            Footsteps::default(),
+            LockOn::default(),
             GameObject::Player,
         ))
         .id();
@@ -21,6 +21,7 @@ pub(crate) mod combat;
 #[cfg(feature = "dev")]
 pub(crate) mod dev;
 pub(crate) mod file_system_interaction;
+pub(crate) mod footsteps;
 pub(crate) mod ingame_menu;
 pub(crate) mod level_instantiation;
 pub(crate) mod menu;
@@ -28,6 +29,7 @@ pub(crate) mod movement;
 pub(crate) mod particles;
 pub(crate) mod player_control;
 pub(crate) mod shader;
+pub(crate) mod simulation;
 pub(crate) mod util;
 pub(crate) mod world_interaction;
 pub(crate) mod spatial_audio;
@@ -36,6 +38,7 @@ use crate::bevy_config::bevy_config_plugin;
 #[cfg(feature = "dev")]
 use crate::dev::dev_plugin;
 use crate::file_system_interaction::file_system_interaction_plugin;
+use crate::footsteps::footstep_plugin;
 use crate::ingame_menu::ingame_menu_plugin;
 use crate::level_instantiation::level_instantiation_plugin;
 use crate::menu::menu_plugin;
@@ -45,8 +48,10 @@ use crate::movement::movement_plugin;
 use crate::particles::particle_plugin;
 use crate::player_control::player_control_plugin;
 use crate::shader::shader_plugin;
+use crate::simulation::simulation_plugin;
 use crate::combat::shoot::shooting_plugin;
 use crate::combat::health::health_plugin;
+use crate::combat::hazards::hazard_plugin;
 use crate::world_interaction::world_interaction_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
@@ -67,11 +72,13 @@ enum GameState {
 /// The top-level plugins are:
 /// - [`bevy_config_plugin`]: Sets up the bevy configuration.
 /// - [`menu_plugin`]: Handles the menu.
+/// - [`simulation_plugin`]: Runs the gameplay simulation at a fixed, deterministic rate.
 /// - [`movement_plugin`]: Handles the movement of entities.
 /// - [`player_control_plugin`]: Handles the player's control.
 /// - [`world_interaction_plugin`]: Handles the interaction of entities with the world.
 /// - [`level_instantiation_plugin`]: Handles the creation of levels and objects.
 /// - [`file_system_interaction_plugin`]: Handles the loading and saving of games.
+/// - [`footstep_plugin`]: Handles surface dependent footstep sounds.
 /// - [`shader_plugin`]: Handles the shaders.
 /// - [`dev_plugin`]: Handles the dev tools.
 /// - [`ingame_menu_plugin`]: Handles the ingame menu accessed via ESC.
@@ -94,15 +101,18 @@ impl Plugin for GamePlugin {
         app.add_state::<GameState>()
             .fn_plugin(bevy_config_plugin)
             .fn_plugin(menu_plugin)
+            .fn_plugin(simulation_plugin)
             .fn_plugin(movement_plugin)
             .fn_plugin(player_control_plugin)
             .fn_plugin(world_interaction_plugin)
             .fn_plugin(level_instantiation_plugin)
             .fn_plugin(file_system_interaction_plugin)
+            .fn_plugin(footstep_plugin)
             .fn_plugin(shader_plugin)
             .fn_plugin(ingame_menu_plugin)
             .fn_plugin(shooting_plugin)
-            .fn_plugin(health_plugin);
+            .fn_plugin(health_plugin)
+            .fn_plugin(hazard_plugin);
         #[cfg(feature = "dev")]
         app.fn_plugin(dev_plugin);
         #[cfg(feature = "native")]
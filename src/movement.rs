@@ -1,10 +1,18 @@
 pub(crate) mod general_movement;
+pub(crate) mod ladder;
+pub(crate) mod moving_platform;
 pub(crate) mod navigation;
+pub(crate) mod parkour;
 pub(crate) mod physics;
+pub(crate) mod water;
 
 use crate::movement::general_movement::general_movement_plugin;
+use crate::movement::ladder::ladder_plugin;
+use crate::movement::moving_platform::moving_platform_plugin;
 use crate::movement::navigation::navigation_plugin;
+use crate::movement::parkour::parkour_plugin;
 use crate::movement::physics::physics_plugin;
+use crate::movement::water::water_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
@@ -14,9 +22,17 @@ use seldom_fn_plugin::FnPluginExt;
 /// - [`general_movement_plugin`]: Handles kinematic character controller movement. A "character" in
 /// this sense is anything that behaves in a not-quite completely physical way, like a player, an npc, an elevator, a moving platform, etc.
 /// Contrast this with pure rigidbodies like a ball, a crate, etc.
-/// - [`navigation_plugin`]: Handles npc pathfinding via bevy_pathmesh integration.
+/// - [`moving_platform_plugin`]: Moves kinematic platforms like elevators and trains, which carry the characters standing on them.
+/// - [`ladder_plugin`]: Lets characters climb ladders and makes them usable for npcs.
+/// - [`water_plugin`]: Lets characters swim in and drown in water.
+/// - [`parkour_plugin`]: Lets characters mantle onto ledges and run along walls in levels that allow it.
+/// - [`navigation_plugin`]: Handles npc pathfinding via oxidized_navigation integration.
 pub(crate) fn movement_plugin(app: &mut App) {
     app.fn_plugin(physics_plugin)
         .fn_plugin(general_movement_plugin)
+        .fn_plugin(moving_platform_plugin)
+        .fn_plugin(ladder_plugin)
+        .fn_plugin(water_plugin)
+        .fn_plugin(parkour_plugin)
         .fn_plugin(navigation_plugin);
 }
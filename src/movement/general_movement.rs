@@ -4,10 +4,12 @@ use std::time::Duration;
 
 use bevy_rapier3d::prelude::*;
 mod components;
+use crate::combat::health::Invulnerable;
 use crate::file_system_interaction::config::GameConfig;
-use crate::level_instantiation::spawning::AnimationEntityLink;
+use crate::level_instantiation::spawning::{AnimationEntityLink, GameObject};
+use crate::simulation::{InterpolationSystemSet, SimulationSet};
 use crate::util::smoothness_to_lerp_factor;
-use crate::util::trait_extension::{TransformExt, Vec3Ext};
+use crate::util::trait_extension::{F32Ext, TransformExt, Vec3Ext};
 use crate::GameState;
 use bevy_mod_sysfail::macros::*;
 pub(crate) use components::*;
@@ -29,6 +31,10 @@ pub(crate) use components::*;
 /// The [`Walking`] and [`Jumping`] components are user friendly ways of influencing the corresponding forces.
 /// There is no explicit maximum speed since the damping counteracts all other forces until reaching an equilibrium.
 /// The [`Grounded`] component is used to determine whether the character is on the ground or not.
+/// Adding the [`Climbing`] component suspends gravity and walking, letting the character move freely along [`Climbing::direction`].
+/// The [`Crouching`] component lets a character shrink its capsule and slide when crouching out of a sprint.
+/// Adding the [`Swimming`] component likewise replaces walking, adding buoyancy towards the water surface.
+/// The [`Dashing`] component gives a character a short burst of speed, optionally making it [`Invulnerable`] for a moment.
 /// To influence movement, apply your force by adding it to the character's total [`ExternalForce`] or [`ExternalImpulse`]. This is usually done like this:
 /// - A continuous force like walking: `external_force.force += acceleration * read_mass_properties.0.mass`, with `external_force`: [`ExternalForce`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `acceleration`: [`Vec3`]
 /// - An instantaneous force (i.e. an impulse) like jumping: `external_impulse.impulse += velocity * read_mass_properties.0.mass`, with `external_impulse`: [`ExternalImpulse`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `velocity`: [`Vec3`]
@@ -39,20 +45,40 @@ pub(crate) fn general_movement_plugin(app: &mut App) {
         .register_type::<Jumping>()
         .register_type::<Velocity>()
         .register_type::<Walking>()
+        .register_type::<Climbing>()
+        .register_type::<Crouching>()
+        .register_type::<Dashing>()
+        .register_type::<Swimming>()
         .register_type::<CharacterAnimations>()
         .add_systems(
             (
                 reset_forces_and_impulses,
                 update_grounded,
+                apply_ground_velocity,
+                apply_crouching,
                 apply_jumping,
+                apply_dashing,
                 apply_walking,
+                apply_swimming,
+                apply_climbing,
+                restore_gravity_after_climbing,
                 rotate_characters,
-                play_animations,
-                sync_models,
                 reset_movement_components,
             )
                 .chain()
                 .in_set(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(
+            apply_movement_config
+                .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            (play_animations, sync_models)
+                .after(InterpolationSystemSet)
                 .in_set(OnUpdate(GameState::Playing)),
         );
 }
@@ -60,25 +86,102 @@ pub(crate) fn general_movement_plugin(app: &mut App) {
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub(crate) struct GeneralMovementSystemSet;
 
+/// Applies the movement tuning of [`GameConfig::movement`] to newly spawned characters and, when the config was reloaded, to all of them.
+fn apply_movement_config(
+    config: Res<GameConfig>,
+    mut characters: Query<(Entity, &GameObject, &mut Walking, &mut Jumping)>,
+    added_characters: Query<(), Added<Walking>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_movement_config").entered();
+    let config_changed = config.is_changed();
+    for (entity, game_object, mut walking, mut jumping) in &mut characters {
+        if !config_changed && !added_characters.contains(entity) {
+            continue;
+        }
+        let movement = match game_object {
+            GameObject::Player => &config.movement.player,
+            GameObject::Npc => &config.movement.npc,
+            GameObject::Enemy => &config.movement.enemy,
+            _ => continue,
+        };
+        walking.ground_acceleration = movement.ground_acceleration;
+        walking.sprinting_acceleration = movement.sprinting_acceleration;
+        walking.aerial_acceleration = movement.aerial_acceleration;
+        walking.braking_acceleration = movement.braking_acceleration;
+        walking.stopping_speed = movement.stopping_speed;
+        jumping.speed = movement.jump_speed;
+    }
+}
+
 fn update_grounded(
     mut query: Query<(Entity, &Transform, &Collider, &mut Grounded)>,
+    grounds: Query<(&RigidBody, &Velocity, &GlobalTransform)>,
     rapier_context: Res<RapierContext>,
 ) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("update_grounded").entered();
     for (entity, transform, collider, mut grounded) in &mut query {
         let height = collider.raw.compute_local_aabb().maxs.y;
-        grounded.0 = rapier_context
-            .cast_ray(
-                transform.translation,
-                transform.down(),
-                height + 0.1,
-                true,
-                QueryFilter::new()
-                    .exclude_collider(entity)
-                    .exclude_sensors(),
-            )
-            .is_some();
+        let hit = rapier_context.cast_ray(
+            transform.translation,
+            transform.down(),
+            height + 0.1,
+            true,
+            QueryFilter::new()
+                .exclude_collider(entity)
+                .exclude_sensors(),
+        );
+        let Some((collider_entity, time_of_impact)) = hit else {
+            *grounded = default();
+            continue;
+        };
+        let ground = rapier_context
+            .collider_parent(collider_entity)
+            .unwrap_or(collider_entity);
+        let (ground_velocity, ground_angular_velocity) = match grounds.get(ground) {
+            Ok((RigidBody::KinematicVelocityBased, velocity, ground_transform)) => {
+                let contact_point = transform.translation + transform.down() * time_of_impact;
+                let lever = contact_point - ground_transform.translation();
+                (velocity.linvel + velocity.angvel.cross(lever), velocity.angvel)
+            }
+            _ => (Vec3::ZERO, Vec3::ZERO),
+        };
+        *grounded = Grounded {
+            ground: Some(ground),
+            ground_velocity,
+            ground_angular_velocity,
+        };
+    }
+}
+
+/// Carries characters along with the ground they are standing on. Since the [`Damping`] of a character
+/// works on its absolute velocity, it is counteracted here so that it only slows down movement relative to the ground.
+fn apply_ground_velocity(
+    fixed_time: Res<FixedTime>,
+    mut character_query: Query<(
+        &Grounded,
+        &Damping,
+        &ReadMassProperties,
+        &mut ExternalForce,
+        &mut Transform,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_ground_velocity").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (grounded, damping, mass, mut force, mut transform) in &mut character_query {
+        if !grounded.is_grounded() {
+            continue;
+        }
+        force.force += grounded.ground_velocity * damping.linear_damping * mass.0.mass;
+
+        // Turn along with rotating platforms
+        let up = transform.up();
+        let yaw = grounded.ground_angular_velocity.dot(up) * dt;
+        if !yaw.is_approx_zero() {
+            transform.rotate_axis(up, yaw);
+        }
     }
 }
 
@@ -99,6 +202,10 @@ pub(crate) fn reset_forces_and_impulses(
 pub(crate) fn reset_movement_components(
     mut walking: Query<&mut Walking>,
     mut jumpers: Query<&mut Jumping>,
+    mut climbers: Query<&mut Climbing>,
+    mut crouchers: Query<&mut Crouching>,
+    mut dashers: Query<&mut Dashing>,
+    mut swimmers: Query<&mut Swimming>,
 ) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("reset_movement_components").entered();
@@ -108,6 +215,18 @@ pub(crate) fn reset_movement_components(
     for mut jumper in &mut jumpers {
         jumper.requested = false;
     }
+    for mut climber in &mut climbers {
+        climber.direction = None;
+    }
+    for mut croucher in &mut crouchers {
+        croucher.requested = false;
+    }
+    for mut dasher in &mut dashers {
+        dasher.requested = None;
+    }
+    for mut swimmer in &mut swimmers {
+        swimmer.direction = None;
+    }
 }
 
 pub(crate) fn apply_jumping(
@@ -123,7 +242,7 @@ pub(crate) fn apply_jumping(
     #[cfg(feature = "tracing")]
     let _span = info_span!("apply_jumping").entered();
     for (grounded, mut impulse, mut velocity, mass, jump, transform) in &mut character_query {
-        if jump.requested && grounded.0 {
+        if jump.requested && grounded.is_grounded() {
             let up = transform.up();
             impulse.impulse += up * mass.0.mass * jump.speed;
 
@@ -135,17 +254,72 @@ pub(crate) fn apply_jumping(
     }
 }
 
+pub(crate) fn apply_dashing(
+    fixed_time: Res<FixedTime>,
+    mut commands: Commands,
+    mut character_query: Query<(
+        Entity,
+        &mut Dashing,
+        &Grounded,
+        &mut ExternalImpulse,
+        &mut Velocity,
+        &ReadMassProperties,
+        &Transform,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_dashing").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (entity, mut dashing, grounded, mut impulse, mut velocity, mass, transform) in
+        &mut character_query
+    {
+        dashing.remaining_cooldown_seconds = (dashing.remaining_cooldown_seconds - dt).max(0.0);
+        if grounded.is_grounded() {
+            dashing.air_dashes_used = 0;
+        }
+        let up = transform.up();
+        let Some(direction) = dashing
+            .requested
+            .and_then(|direction| direction.split(up).horizontal.try_normalize())
+        else {
+            continue;
+        };
+        let can_dash_in_air = dashing.air_dashes_used < dashing.max_air_dashes;
+        if dashing.remaining_cooldown_seconds > 0.0 || !(grounded.is_grounded() || can_dash_in_air) {
+            continue;
+        }
+
+        // Kill any horizontal velocity so that dashes are always the same length, no matter where we were going before.
+        velocity.linvel = velocity.linvel.split(up).vertical;
+        impulse.impulse += direction * dashing.speed * mass.0.mass;
+        dashing.remaining_cooldown_seconds = dashing.cooldown_seconds;
+        if !grounded.is_grounded() {
+            dashing.air_dashes_used += 1;
+        }
+        if dashing.invulnerability_seconds > 0.0 {
+            if let Some(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.insert(Invulnerable {
+                    remaining_seconds: dashing.invulnerability_seconds,
+                });
+            }
+        }
+    }
+}
+
 fn rotate_characters(
-    time: Res<Time>,
-    mut player_query: Query<(&Velocity, &mut Transform)>,
+    fixed_time: Res<FixedTime>,
+    mut player_query: Query<(&Velocity, &Grounded, &mut Transform)>,
     config: Res<GameConfig>,
 ) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("rotate_characters").entered();
-    let dt = time.delta_seconds();
-    for (velocity, mut transform) in player_query.iter_mut() {
+    let dt = fixed_time.period.as_secs_f32();
+    for (velocity, grounded, mut transform) in player_query.iter_mut() {
         let up = transform.up();
-        let horizontal_movement = velocity.linvel.split(up).horizontal;
+        let horizontal_movement = grounded
+            .get_relative_velocity(velocity.linvel)
+            .split(up)
+            .horizontal;
         if horizontal_movement.is_approx_zero() {
             continue;
         }
@@ -168,22 +342,29 @@ fn play_animations(
         &Grounded,
         &AnimationEntityLink,
         &CharacterAnimations,
+        Option<&Climbing>,
     )>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("play_animations").entered();
-    for (velocity, transform, grounded, animation_entity_link, animations) in characters.iter() {
+    for (velocity, transform, grounded, animation_entity_link, animations, climbing) in
+        characters.iter()
+    {
         let mut animation_player = animation_player
             .get_mut(animation_entity_link.0)
             .context("animation_entity_link held entity without animation player")?;
 
-        let has_horizontal_movement = !velocity
-            .linvel
+        let has_horizontal_movement = !grounded
+            .get_relative_velocity(velocity.linvel)
             .split(transform.up())
             .horizontal
             .is_approx_zero();
 
-        if !grounded.0 {
+        if climbing.is_some() {
+            animation_player
+                .play_with_transition(animations.climb.clone_weak(), Duration::from_secs_f32(0.2))
+                .repeat();
+        } else if !grounded.is_grounded() {
             animation_player
                 .play_with_transition(animations.aerial.clone_weak(), Duration::from_secs_f32(0.2))
                 .repeat();
@@ -201,28 +382,42 @@ fn play_animations(
 }
 
 pub(crate) fn apply_walking(
-    mut character_query: Query<(
-        &mut ExternalForce,
-        &Walking,
-        &mut Velocity,
-        &Grounded,
-        &ReadMassProperties,
-        &Transform,
-    )>,
+    mut character_query: Query<
+        (
+            &mut ExternalForce,
+            &Walking,
+            &mut Velocity,
+            &Grounded,
+            &ReadMassProperties,
+            &Transform,
+            Option<&Crouching>,
+        ),
+        (Without<Climbing>, Without<Swimming>),
+    >,
 ) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("apply_walking").entered();
-    for (mut force, walking, mut velocity, grounded, mass, transform) in &mut character_query {
+    for (mut force, walking, mut velocity, grounded, mass, transform, crouching) in
+        &mut character_query
+    {
+        let crouching = crouching.filter(|crouching| crouching.active);
+        if crouching.map_or(false, |crouching| crouching.slide.is_some()) {
+            // Sliding brings its own friction
+            continue;
+        }
+        let speed_factor = crouching.map_or(1.0, |crouching| crouching.speed_factor);
         let mass = mass.0.mass;
-        if let Some(acceleration) = walking.get_acceleration(grounded.0) {
-            let walking_force = acceleration * mass;
+        if let Some(acceleration) = walking.get_acceleration(grounded.is_grounded()) {
+            let walking_force = acceleration * speed_factor * mass;
             force.force += walking_force;
-        } else if grounded.0 {
-            let velocity_components = velocity.linvel.split(transform.up());
+        } else if grounded.is_grounded() {
+            let up = transform.up();
+            let velocity_components = grounded.get_relative_velocity(velocity.linvel).split(up);
             if velocity_components.horizontal.length_squared()
                 < walking.stopping_speed * walking.stopping_speed
             {
-                velocity.linvel = velocity_components.vertical;
+                velocity.linvel = velocity.linvel.split(up).vertical
+                    + grounded.ground_velocity.split(up).horizontal;
             } else if let Some(braking_direction) =
                 velocity_components.horizontal.try_normalize().map(|v| -v)
             {
@@ -233,20 +428,188 @@ pub(crate) fn apply_walking(
     }
 }
 
+pub(crate) fn apply_crouching(
+    fixed_time: Res<FixedTime>,
+    mut character_query: Query<(
+        Entity,
+        &mut Crouching,
+        &mut Collider,
+        &mut Transform,
+        &mut Velocity,
+        &mut ExternalForce,
+        &Walking,
+        &Grounded,
+        &ReadMassProperties,
+    )>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_crouching").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (
+        entity,
+        mut crouching,
+        mut collider,
+        mut transform,
+        mut velocity,
+        mut force,
+        walking,
+        grounded,
+        mass,
+    ) in &mut character_query
+    {
+        let up = transform.up();
+        let offset = crouching.get_center_offset();
+        if crouching.requested && !crouching.active {
+            crouching.active = true;
+            *collider = Collider::capsule_y(crouching.crouching_height / 2., crouching.radius);
+            // Keep the feet where they are
+            transform.translation -= up * offset;
+
+            let horizontal_velocity = velocity.linvel.split(up).horizontal;
+            if grounded.is_grounded()
+                && walking.sprinting
+                && horizontal_velocity.length_squared() > crouching.slide_min_speed.squared()
+            {
+                velocity.linvel += horizontal_velocity.normalize() * crouching.slide_boost;
+                crouching.slide = Some(default());
+            }
+        } else if !crouching.requested && crouching.active {
+            let standing_center = transform.translation + up * offset;
+            if has_room_to_stand(
+                entity,
+                standing_center,
+                transform.rotation,
+                &crouching,
+                &rapier_context,
+            ) {
+                crouching.active = false;
+                crouching.slide = None;
+                *collider = Collider::capsule_y(crouching.standing_height / 2., crouching.radius);
+                transform.translation = standing_center;
+            }
+        }
+
+        if let Some(mut slide) = crouching.slide.clone() {
+            slide.elapsed_seconds += dt;
+            let horizontal_velocity = velocity.linvel.split(up).horizontal;
+            let progress = (slide.elapsed_seconds / crouching.slide_seconds).min(1.0);
+            let has_stopped =
+                horizontal_velocity.length_squared() < walking.stopping_speed.squared();
+            crouching.slide = if grounded.is_grounded() && progress < 1.0 && !has_stopped {
+                // Friction ramps up quadratically so that the slide keeps most of its momentum at first
+                let friction = crouching.slide_friction_start
+                    + (crouching.slide_friction_end - crouching.slide_friction_start)
+                        * progress.squared();
+                force.force -= horizontal_velocity.normalize() * friction * mass.0.mass;
+                Some(slide)
+            } else {
+                None
+            };
+        }
+    }
+}
+
+fn has_room_to_stand(
+    entity: Entity,
+    standing_center: Vec3,
+    rotation: Quat,
+    crouching: &Crouching,
+    rapier_context: &RapierContext,
+) -> bool {
+    // Slightly thinner and lifted off the ground so that merely touching the floor or a wall does not count as blocked
+    const MARGIN: f32 = 0.05;
+    let shape = Collider::capsule_y(crouching.standing_height / 2., crouching.radius - MARGIN);
+    let mut is_blocked = false;
+    rapier_context.intersections_with_shape(
+        standing_center + rotation * Vec3::Y * MARGIN,
+        rotation,
+        &shape,
+        QueryFilter::new()
+            .exclude_collider(entity)
+            .exclude_sensors(),
+        |_entity| {
+            is_blocked = true;
+            false
+        },
+    );
+    !is_blocked
+}
+
+pub(crate) fn apply_swimming(
+    mut character_query: Query<(
+        &Swimming,
+        &Walking,
+        &mut ExternalForce,
+        &ReadMassProperties,
+        &Transform,
+    )>,
+    rapier_config: Res<RapierConfiguration>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_swimming").entered();
+    let gravity = rapier_config.gravity.length();
+    for (swimming, walking, mut force, mass, transform) in &mut character_query {
+        let mass = mass.0.mass;
+        let buoyancy = transform.up() * gravity * swimming.buoyancy * swimming.submersion;
+        force.force += buoyancy * mass;
+        if let Some(direction) = swimming.direction.or(walking.direction) {
+            force.force += direction * swimming.acceleration * mass;
+        }
+    }
+}
+
+pub(crate) fn apply_climbing(
+    mut character_query: Query<(&Climbing, &mut Velocity, &mut GravityScale)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_climbing").entered();
+    for (climbing, mut velocity, mut gravity_scale) in &mut character_query {
+        gravity_scale.0 = 0.0;
+        velocity.linvel = climbing
+            .direction
+            .map(|direction| direction * climbing.speed)
+            .unwrap_or_default();
+    }
+}
+
+fn restore_gravity_after_climbing(
+    mut removed_climbing: RemovedComponents<Climbing>,
+    mut gravity_scales: Query<&mut GravityScale>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("restore_gravity_after_climbing").entered();
+    for entity in removed_climbing.iter() {
+        if let Ok(mut gravity_scale) = gravity_scales.get_mut(entity) {
+            gravity_scale.0 = 1.0;
+        }
+    }
+}
+
 #[sysfail(log(level = "error"))]
 fn sync_models(
     time: Res<Time>,
     mut commands: Commands,
-    without_model: Query<(&Transform, &Visibility), Without<Model>>,
+    without_model: Query<(&Transform, &Visibility, Option<&Crouching>), Without<Model>>,
     mut with_model: Query<(Entity, &mut Transform, &mut Visibility, &Model)>,
     game_config: Res<GameConfig>,
 ) -> Result<()> {
     let dt = time.delta_seconds();
     for (model_entity, mut model_transform, mut visibility, model) in with_model.iter_mut() {
-        if let Ok((target_transform, target_visibility)) = without_model.get(model.target) {
+        if let Ok((target_transform, target_visibility, crouching)) =
+            without_model.get(model.target)
+        {
+            // The model is placed relative to the center of a standing character, so undo the lowered center when crouching
+            let crouch_offset = crouching
+                .filter(|crouching| crouching.active)
+                .map_or(0.0, |crouching| crouching.get_center_offset());
+            let target_transform = Transform {
+                translation: target_transform.translation + target_transform.up() * crouch_offset,
+                ..*target_transform
+            };
             let smoothness = game_config.characters.model_sync_smoothing;
             let factor = smoothness_to_lerp_factor(smoothness, dt);
-            *model_transform = model_transform.lerp(*target_transform, factor);
+            *model_transform = model_transform.lerp(target_transform, factor);
             *visibility = *target_visibility;
         } else {
             if let Some(entity_commands) = commands.get_entity(model_entity) {
@@ -58,6 +58,8 @@ pub(crate) struct Model {
     pub(crate) target: Entity,
 }
 
+/// The accelerations of players, npcs and enemies are tuned in [`GameConfig::movement`](crate::file_system_interaction::config::GameConfig::movement),
+/// which overrides the defaults when the character spawns.
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
 pub(crate) struct Walking {
@@ -108,8 +110,28 @@ impl Default for Walking {
 
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Default, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
-pub(crate) struct Grounded(pub(crate) bool);
+pub(crate) struct Grounded {
+    /// The entity the character is standing on this tick, if any. For colliders attached to a rigid body, this is the rigid body.
+    pub(crate) ground: Option<Entity>,
+    /// Velocity of the ground under the character's feet in m/s. Only kinematic bodies like moving platforms have one.
+    /// Characters move relative to it, so that they are carried along.
+    pub(crate) ground_velocity: Vec3,
+    /// Angular velocity of the ground in rad/s
+    pub(crate) ground_angular_velocity: Vec3,
+}
+
+impl Grounded {
+    pub(crate) fn is_grounded(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    /// Velocity relative to the ground, which is what the character perceives as its own movement
+    pub(crate) fn get_relative_velocity(&self, velocity: Vec3) -> Vec3 {
+        velocity - self.ground_velocity
+    }
+}
 
+/// Like [`Walking`], the jump speed of characters is tuned in [`GameConfig::movement`](crate::file_system_interaction::config::GameConfig::movement).
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
 pub(crate) struct Jumping {
@@ -128,10 +150,199 @@ impl Default for Jumping {
     }
 }
 
+/// While present, gravity is suspended and the character moves along [`Climbing::direction`] instead of [`Walking`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Climbing {
+    /// Speed of climbing in m/s
+    pub(crate) speed: f32,
+    /// Direction in which we want to climb this tick. When not normalized, the speed will be scaled accordingly.
+    pub(crate) direction: Option<Vec3>,
+}
+
+impl Default for Climbing {
+    fn default() -> Self {
+        Self {
+            speed: 2.,
+            direction: None,
+        }
+    }
+}
+
+/// Present while a character is in water deep enough to swim. Suspends [`Walking`] in favor of moving along [`Swimming::direction`],
+/// while [`Swimming::buoyancy`] pushes the character towards the surface.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Swimming {
+    /// Acceleration while swimming in m/s²
+    pub(crate) acceleration: f32,
+    /// Direction in which we want to swim this tick, including up and down. When not normalized, the acceleration will be scaled accordingly.
+    /// Falls back to [`Walking::direction`] when [`Option::None`], so that agents which only know how to walk still get around.
+    pub(crate) direction: Option<Vec3>,
+    /// Upward acceleration when fully submerged, relative to gravity. Values slightly above 1 let characters float with their head above water.
+    pub(crate) buoyancy: f32,
+    /// Linear damping while swimming, replacing the character's usual [`Damping`]
+    pub(crate) linear_damping: f32,
+    /// Linear damping to restore when leaving the water
+    pub(crate) dry_linear_damping: f32,
+    /// Height of the surface of the water we are in
+    pub(crate) surface_height: f32,
+    /// How much of the character is below the surface, from 0 to 1
+    pub(crate) submersion: f32,
+}
+
+impl Default for Swimming {
+    fn default() -> Self {
+        Self {
+            acceleration: 8.,
+            direction: None,
+            buoyancy: 1.2,
+            linear_damping: 3.,
+            dry_linear_damping: 1.5,
+            surface_height: 0.,
+            submersion: 0.,
+        }
+    }
+}
+
+/// Lets a character crouch by shrinking its capsule. Crouching while sprinting on the ground starts a [`Slide`] instead of a regular crouch.
+/// Only add this to characters spawned with [`CharacterControllerBundle::capsule`] using the same `height` and `radius`.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Crouching {
+    /// Was crouching requested this tick?
+    pub(crate) requested: bool,
+    /// Whether the collider is currently shrunk. Stays `true` after crouching is no longer requested until there is room to stand up.
+    pub(crate) active: bool,
+    /// Height of the capsule while standing, as passed to [`CharacterControllerBundle::capsule`]
+    pub(crate) standing_height: f32,
+    /// Height of the capsule while crouching
+    pub(crate) crouching_height: f32,
+    /// Radius of the capsule, as passed to [`CharacterControllerBundle::capsule`]
+    pub(crate) radius: f32,
+    /// Factor applied to [`Walking`] acceleration while crouching
+    pub(crate) speed_factor: f32,
+    /// Factor applied to the loudness of footsteps while crouching, both to their sound and to how far
+    /// NPCs with [`Hearing`](crate::movement::navigation::hearing::Hearing) notice them
+    pub(crate) noise_factor: f32,
+    /// Minimum horizontal speed in m/s at which crouching while sprinting turns into a slide
+    pub(crate) slide_min_speed: f32,
+    /// Speed in m/s added in the direction of movement when starting a slide
+    pub(crate) slide_boost: f32,
+    /// Deceleration in m/s² at the start of a slide
+    pub(crate) slide_friction_start: f32,
+    /// Deceleration in m/s² at the end of a slide
+    pub(crate) slide_friction_end: f32,
+    /// How long it takes for the friction to go from [`Crouching::slide_friction_start`] to [`Crouching::slide_friction_end`]
+    pub(crate) slide_seconds: f32,
+    pub(crate) slide: Option<Slide>,
+}
+
+impl Crouching {
+    pub(crate) fn new(standing_height: f32, radius: f32) -> Self {
+        Self {
+            standing_height,
+            crouching_height: standing_height * 0.4,
+            radius,
+            ..default()
+        }
+    }
+
+    /// How far the center of the capsule moves down when crouching, assuming the feet stay in place
+    pub(crate) fn get_center_offset(&self) -> f32 {
+        (self.standing_height - self.crouching_height) / 2.
+    }
+
+    pub(crate) fn get_noise_factor(&self) -> f32 {
+        if self.active {
+            self.noise_factor
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for Crouching {
+    fn default() -> Self {
+        Self {
+            requested: false,
+            active: false,
+            standing_height: 1.0,
+            crouching_height: 0.4,
+            radius: 0.4,
+            speed_factor: 0.5,
+            noise_factor: 0.3,
+            slide_min_speed: 4.,
+            slide_boost: 1.5,
+            slide_friction_start: 1.,
+            slide_friction_end: 8.,
+            slide_seconds: 0.8,
+            slide: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct Slide {
+    pub(crate) elapsed_seconds: f32,
+}
+
+/// A short burst of horizontal speed with a cooldown. Can be used a limited number of times while airborne.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Dashing {
+    /// Direction in which a dash was requested this tick, if any. Does not need to be normalized.
+    pub(crate) requested: Option<Vec3>,
+    /// Speed of the dash in m/s
+    pub(crate) speed: f32,
+    /// How long a dash counts as ongoing, e.g. for effects
+    pub(crate) duration_seconds: f32,
+    /// Time between two dashes
+    pub(crate) cooldown_seconds: f32,
+    /// Time until the next dash is possible
+    pub(crate) remaining_cooldown_seconds: f32,
+    /// How long the character cannot be damaged after starting a dash. Zero disables invulnerability.
+    pub(crate) invulnerability_seconds: f32,
+    /// How many dashes can be done before touching the ground again
+    pub(crate) max_air_dashes: u32,
+    pub(crate) air_dashes_used: u32,
+}
+
+impl Dashing {
+    pub(crate) fn is_dashing(&self) -> bool {
+        self.remaining_cooldown_seconds > self.cooldown_seconds - self.duration_seconds
+    }
+
+    /// How far the cooldown has progressed, where `1.0` means a dash is ready
+    pub(crate) fn get_readiness(&self) -> f32 {
+        if self.cooldown_seconds <= 0.0 {
+            return 1.0;
+        }
+        1.0 - (self.remaining_cooldown_seconds / self.cooldown_seconds).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for Dashing {
+    fn default() -> Self {
+        Self {
+            requested: None,
+            speed: 9.,
+            duration_seconds: 0.2,
+            cooldown_seconds: 1.,
+            remaining_cooldown_seconds: 0.,
+            invulnerability_seconds: 0.3,
+            max_air_dashes: 1,
+            air_dashes_used: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
 #[reflect(Component)]
 pub(crate) struct CharacterAnimations {
     pub(crate) idle: Handle<AnimationClip>,
     pub(crate) walk: Handle<AnimationClip>,
     pub(crate) aerial: Handle<AnimationClip>,
+    pub(crate) climb: Handle<AnimationClip>,
 }
@@ -0,0 +1,208 @@
+use crate::movement::general_movement::{Climbing, GeneralMovementSystemSet, Grounded, Walking};
+use crate::movement::navigation::navlink::{NavLink, NavLinkKind};
+use crate::movement::physics::SensorVolumeReader;
+use crate::simulation::SimulationSet;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Speed in m/s with which a climber is flung off a ladder when letting go of it by jumping
+const DETACH_SPEED: f32 = 2.5;
+/// Speed in m/s with which a climber is pushed onto the ledge when climbing off the top of a ladder
+const TOP_EXIT_SPEED: f32 = 2.0;
+
+/// Handles ladders and other climbable volumes. Characters with a [`LadderClimber`] grab a [`Ladder`] when walking into it
+/// or falling through it, after which they are moved by [`Climbing`] instead of [`Walking`].
+/// They let go when climbing off the top, jumping, or stepping off onto the ground at the bottom.
+/// For navmesh agents, every ladder with a marked top exit also becomes a bidirectional climbing [`NavLink`].
+pub(crate) fn ladder_plugin(app: &mut App) {
+    app.register_type::<Ladder>()
+        .register_type::<LadderClimber>()
+        .add_system(
+            read_ladders
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_system(
+            grab_ladders
+                .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// A climbable volume, authored in the level by tagging an object with `[ladder]`. Its meshes become invisible convex sensor colliders.
+/// If the object has a child tagged with `[navlink_end]`, agents can climb from the bottom of the volume to that child and back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Ladder;
+
+/// Lets a character grab onto [`Ladder`]s by walking into them.
+/// Needs [`Walking`] and [`Grounded`] to tell walking into a ladder apart from walking past it.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct LadderClimber {
+    /// The ladder that is currently being climbed
+    pub(crate) ladder: Option<Entity>,
+    /// Was letting go of the ladder requested this tick?
+    pub(crate) detach_requested: bool,
+    /// Seconds during which no ladder can be grabbed after letting go, so that we don't immediately grab it again
+    pub(crate) regrab_cooldown_seconds: f32,
+    pub(crate) remaining_cooldown_seconds: f32,
+}
+
+impl Default for LadderClimber {
+    fn default() -> Self {
+        Self {
+            ladder: None,
+            detach_requested: false,
+            regrab_cooldown_seconds: 0.5,
+            remaining_cooldown_seconds: 0.0,
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn read_ladders(
+    mut commands: Commands,
+    mut sensor_volumes: SensorVolumeReader,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    global_transforms: Query<&GlobalTransform>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_ladders").entered();
+    for (parent, world_points) in sensor_volumes.read("[ladder]", |_points| Ladder)? {
+        let top_exit = children
+            .get(parent)
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .find(|child| {
+                names
+                    .get(**child)
+                    .map(|name| name.to_lowercase().contains("[navlink_end]"))
+                    .unwrap_or_default()
+            });
+        if let Some(top_exit) = top_exit && !world_points.is_empty() {
+            let count = world_points.len() as f32;
+            let center = world_points.iter().sum::<Vec3>() / count;
+            let bottom = world_points
+                .iter()
+                .map(|point| point.y)
+                .fold(f32::INFINITY, f32::min);
+            let end = global_transforms
+                .get(*top_exit)
+                .context("Failed to get global transform of ladder exit")?
+                .translation();
+            if let Some(mut entity_commands) = commands.get_entity(parent) {
+                entity_commands.insert(NavLink {
+                    kind: NavLinkKind::Climb,
+                    start: Vec3::new(center.x, bottom, center.z),
+                    end,
+                    bidirectional: true,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn grab_ladders(
+    fixed_time: Res<FixedTime>,
+    mut commands: Commands,
+    mut climbers: Query<(
+        Entity,
+        &mut LadderClimber,
+        &Transform,
+        &mut Velocity,
+        &Walking,
+        &Grounded,
+        Option<&Climbing>,
+    )>,
+    ladders: Query<(&Collider, &GlobalTransform), With<Ladder>>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("grab_ladders").entered();
+    for (entity, mut climber, transform, mut velocity, walking, grounded, climbing) in &mut climbers
+    {
+        climber.remaining_cooldown_seconds =
+            (climber.remaining_cooldown_seconds - fixed_time.period.as_secs_f32()).max(0.0);
+        let touched_ladder = rapier_context
+            .intersections_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == entity { b } else { a })
+            .find(|other| ladders.contains(*other));
+
+        let Some(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+        let up = transform.up();
+        let is_walking_towards = |ladder| {
+            walking.direction.map_or(false, |direction| {
+                is_towards_ladder(ladder, transform.translation, direction, up, &ladders)
+            })
+        };
+        let is_climbing_down = climbing
+            .and_then(|climbing| climbing.direction)
+            .map_or(false, |direction| direction.dot(up) < 0.0);
+        let is_walking_away =
+            walking.direction.is_some() && !touched_ladder.map_or(false, is_walking_towards);
+        match (climber.ladder, touched_ladder) {
+            // Walking past a ladder or standing at its foot should not grab it
+            (None, Some(ladder))
+                if climber.remaining_cooldown_seconds <= 0.0
+                    && (!grounded.is_grounded() || is_walking_towards(ladder)) =>
+            {
+                climber.ladder = Some(ladder);
+                velocity.linvel = Vec3::ZERO;
+                entity_commands.insert(Climbing::default());
+            }
+            (Some(_), None) => {
+                // Climbed off either end of the ladder
+                if velocity.linvel.dot(up) > 0.0 {
+                    velocity.linvel = (up + transform.forward()) * TOP_EXIT_SPEED;
+                }
+                climber.ladder = None;
+                entity_commands.remove::<Climbing>();
+            }
+            (Some(_), Some(_)) if climber.detach_requested => {
+                velocity.linvel = (up + transform.back()) * DETACH_SPEED;
+                climber.ladder = None;
+                climber.remaining_cooldown_seconds = climber.regrab_cooldown_seconds;
+                entity_commands.remove::<Climbing>();
+            }
+            // Reached the ground at the bottom of the ladder
+            (Some(_), Some(_))
+                if grounded.is_grounded() && (is_climbing_down || is_walking_away) =>
+            {
+                climber.ladder = None;
+                entity_commands.remove::<Climbing>();
+            }
+            _ => {}
+        }
+        climber.detach_requested = false;
+    }
+}
+
+/// Whether the direction points towards the center of the ladder, ignoring height
+fn is_towards_ladder(
+    ladder: Entity,
+    position: Vec3,
+    direction: Vec3,
+    up: Vec3,
+    ladders: &Query<(&Collider, &GlobalTransform), With<Ladder>>,
+) -> bool {
+    let Ok((collider, transform)) = ladders.get(ladder) else {
+        return false;
+    };
+    let local_center = collider.raw.compute_local_aabb().center();
+    let center =
+        transform.transform_point(Vec3::new(local_center.x, local_center.y, local_center.z));
+    let to_ladder = center - position;
+    let to_ladder = to_ladder - up * to_ladder.dot(up);
+    direction.dot(to_ladder) > 0.0
+}
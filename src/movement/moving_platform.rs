@@ -0,0 +1,183 @@
+use crate::movement::general_movement::GeneralMovementSystemSet;
+use crate::simulation::SimulationSet;
+use crate::util::trait_extension::F32Ext;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Handles kinematic platforms that move along waypoints or rotate, like elevators, trains or turntables.
+/// Characters standing on them are carried along by [`Grounded::ground_velocity`](crate::movement::general_movement::Grounded::ground_velocity).
+pub(crate) fn moving_platform_plugin(app: &mut App) {
+    app.register_type::<MovingPlatform>()
+        .add_system(
+            read_moving_platforms
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_system(
+            move_platforms
+                .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// A kinematic body that travels along [`MovingPlatform::waypoints`] in a loop and/or rotates with a constant angular velocity.
+/// Authored in the level by tagging an object with `[platform]`. Its children tagged with `[waypoint]` are visited in order of their names,
+/// starting from the platform's own position. The following tags are optional:
+/// - `[platform_speed: 2.5]`: Travel speed in m/s
+/// - `[platform_wait: 1.0]`: Seconds to wait at every waypoint, e.g. for elevators
+/// - `[platform_rotate: 0, 45, 0]`: Angular velocity in degrees per second around the x, y and z axes
+///
+/// The platform's colliders should be tagged with `[collider]` and `[dynamic]` so that the navmesh under them is kept up-to-date.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct MovingPlatform {
+    /// Global positions to travel through. Empty for platforms that only rotate.
+    pub(crate) waypoints: Vec<Vec3>,
+    /// Speed in m/s
+    pub(crate) speed: f32,
+    pub(crate) wait_seconds: f32,
+    /// Angular velocity in rad/s
+    pub(crate) angular_velocity: Vec3,
+    pub(crate) next_waypoint: usize,
+    pub(crate) remaining_wait_seconds: f32,
+}
+
+impl Default for MovingPlatform {
+    fn default() -> Self {
+        Self {
+            waypoints: default(),
+            speed: 2.0,
+            wait_seconds: 0.0,
+            angular_velocity: Vec3::ZERO,
+            next_waypoint: 0,
+            remaining_wait_seconds: 0.0,
+        }
+    }
+}
+
+static SPEED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[platform_speed:\s*(\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile platform speed regex")
+});
+
+static WAIT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[platform_wait:\s*(\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile platform wait regex")
+});
+
+static ROTATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[platform_rotate:\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile platform rotation regex")
+});
+
+#[sysfail(log(level = "error"))]
+fn read_moving_platforms(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name, &GlobalTransform, Option<&Children>), Added<Name>>,
+    names: Query<&Name>,
+    global_transforms: Query<&GlobalTransform>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_moving_platforms").entered();
+    for (entity, name, global_transform, children) in &added_name {
+        let name = name.to_lowercase();
+        if !name.contains("[platform]") {
+            continue;
+        }
+        let mut platform = MovingPlatform::default();
+        if let Some(captures) = SPEED_REGEX.captures(&name) {
+            platform.speed = captures[1]
+                .parse()
+                .with_context(|| format!("Failed to parse platform speed in: {name}"))?;
+        }
+        if let Some(captures) = WAIT_REGEX.captures(&name) {
+            platform.wait_seconds = captures[1]
+                .parse()
+                .with_context(|| format!("Failed to parse platform wait in: {name}"))?;
+        }
+        if let Some(captures) = ROTATE_REGEX.captures(&name) {
+            let mut degrees = [0.0_f32; 3];
+            for (axis, degree) in degrees.iter_mut().enumerate() {
+                *degree = captures[axis + 1]
+                    .parse()
+                    .with_context(|| format!("Failed to parse platform rotation in: {name}"))?;
+            }
+            platform.angular_velocity = Vec3::from(degrees.map(f32::to_radians));
+        }
+
+        let mut waypoints = children
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| {
+                let child_name = names.get(*child).ok()?.to_lowercase();
+                child_name.contains("[waypoint]").then_some((child_name, *child))
+            })
+            .collect::<Vec<_>>();
+        waypoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if !waypoints.is_empty() {
+            platform.waypoints.push(global_transform.translation());
+            for (_, waypoint) in waypoints {
+                let translation = global_transforms
+                    .get(waypoint)
+                    .context("Failed to get global transform of platform waypoint")?
+                    .translation();
+                platform.waypoints.push(translation);
+            }
+            // The platform starts at the first waypoint
+            platform.next_waypoint = 1;
+        }
+        if platform.waypoints.is_empty() && platform.angular_velocity == Vec3::ZERO {
+            bail!("Moving platform \"{name}\" has neither [waypoint] children nor a [platform_rotate] tag");
+        }
+
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert((
+                platform,
+                RigidBody::KinematicVelocityBased,
+                Velocity::default(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn move_platforms(
+    fixed_time: Res<FixedTime>,
+    mut platforms: Query<(&mut MovingPlatform, &mut Velocity, &GlobalTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_platforms").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    if dt.is_approx_zero() {
+        return;
+    }
+    for (mut platform, mut velocity, transform) in &mut platforms {
+        velocity.angvel = platform.angular_velocity;
+        if platform.waypoints.is_empty() {
+            continue;
+        }
+        if platform.remaining_wait_seconds > 0.0 {
+            platform.remaining_wait_seconds -= dt;
+            velocity.linvel = Vec3::ZERO;
+            continue;
+        }
+        let target = platform.waypoints[platform.next_waypoint];
+        let to_target = target - transform.translation();
+        let step = platform.speed * dt;
+        if to_target.length_squared() <= step.squared() {
+            // Land exactly on the waypoint instead of overshooting it
+            velocity.linvel = to_target / dt;
+            platform.next_waypoint = (platform.next_waypoint + 1) % platform.waypoints.len();
+            platform.remaining_wait_seconds = platform.wait_seconds;
+        } else {
+            velocity.linvel = to_target.normalize() * platform.speed;
+        }
+    }
+}
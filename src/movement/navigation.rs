@@ -1,29 +1,72 @@
 #[cfg(feature = "dev")]
 use crate::dev::dev_editor::DevEditorWindow;
 use crate::level_instantiation::spawning::objects::npc;
-use crate::movement::general_movement::{GeneralMovementSystemSet, Walking};
+use crate::movement::general_movement::GeneralMovementSystemSet;
+use crate::movement::navigation::hearing::{listen_for_footsteps, Hearing};
+use crate::movement::navigation::navlink::{
+    find_route, read_navlinks, traverse_navlinks, NavLink, NavLinkTraversal, Route,
+};
+#[cfg(feature = "dev")]
+use crate::movement::navigation::navmesh_cache::bake_generated_navmesh;
+use crate::movement::navigation::navmesh_cache::{
+    load_or_generate_navmesh, start_navmesh_bake, NavMeshBakeState, NavMeshInput,
+};
+use crate::movement::navigation::navmesh::read_navmesh;
+use crate::movement::navigation::obstacle::{
+    activate_inputs_near_obstacles, track_obstacle_changes, NavMeshObstacle,
+    NavMeshObstacleChanged,
+};
+use crate::movement::navigation::steering::{apply_steering, Steering};
 use crate::player_control::player_embodiment::Player;
+use crate::simulation::SimulationSet;
 use crate::util::trait_extension::{F32Ext, Vec3Ext};
 use crate::GameState;
 #[cfg(feature = "dev")]
 use anyhow::Context;
 use anyhow::Result;
 use bevy::prelude::*;
+use bevy::transform::TransformSystem;
 use bevy_mod_sysfail::macros::*;
 #[cfg(feature = "dev")]
 use bevy_prototype_debug_lines::DebugLines;
-use oxidized_navigation::{
-    query::{find_path, perform_string_pulling_on_path},
-    NavMesh, NavMeshSettings, OxidizedNavigationPlugin,
-};
+use oxidized_navigation::{NavMesh, NavMeshSettings, OxidizedNavigationPlugin};
 use serde::{Deserialize, Serialize};
 
+pub(crate) mod hearing;
+pub(crate) mod navlink;
+pub(crate) mod navmesh;
+pub(crate) mod navmesh_cache;
+pub(crate) mod obstacle;
+pub(crate) mod steering;
+
 /// Manually tweaked
 const CELL_WIDTH: f32 = 0.4 * npc::RADIUS;
+/// Paths are re-planned at least this often, since the target keeps moving and the navmesh is regenerated asynchronously
+const REPLAN_INTERVAL_SECONDS: f32 = 0.5;
+/// How far the target is allowed to move before the path to it is re-planned immediately
+const REPLAN_TARGET_DISTANCE: f32 = 1.0;
+/// Waypoints closer than this are considered reached
+const WAYPOINT_RADIUS: f32 = 0.3;
 
-/// Handles NPC pathfinding. Currently, all entities with the [`Follower`] component will follow the [`Player`].
+/// Handles NPC pathfinding. Currently, all entities with the [`Follower`] component will follow the [`Player`],
+/// or, if they have [`Hearing`], walk to where they last heard them.
+/// The path only determines where an agent wants to go. The actual [`Walking::direction`](crate::movement::general_movement::Walking::direction)
+/// is decided by [`Steering`], which adds local avoidance of other agents, the player and static geometry.
+/// Where the navmesh itself is not connected, agents can still get around by traversing a [`NavLink`].
+/// The navmesh of a level is loaded from `assets/levels/<level>.navmesh.ron` if it is up-to-date with the level's colliders,
+/// otherwise it is generated at runtime and, in dev builds, baked to that file afterwards.
+/// Level designers can add to or override the generated navmesh by tagging meshes with `[navmesh]`, see [`read_navmesh`].
+/// Colliders that move or vanish at runtime are tracked as [`NavMeshObstacle`]s, which make agents re-plan paths that cross them.
 pub(crate) fn navigation_plugin(app: &mut App) {
-    app.add_plugin(OxidizedNavigationPlugin)
+    app.register_type::<Steering>()
+        .register_type::<Hearing>()
+        .register_type::<NavLink>()
+        .register_type::<NavLinkTraversal>()
+        .register_type::<NavMeshInput>()
+        .register_type::<NavMeshObstacle>()
+        .init_resource::<NavMeshBakeState>()
+        .add_event::<NavMeshObstacleChanged>()
+        .add_plugin(OxidizedNavigationPlugin)
         // consts manually tweaked
         .insert_resource(NavMeshSettings {
             cell_width: CELL_WIDTH,
@@ -40,21 +83,73 @@ pub(crate) fn navigation_plugin(app: &mut App) {
             max_contour_simplification_error: 1.3,
             max_edge_length: 100,
         })
-        .add_system(
-            query_mesh
+        .add_systems(
+            (
+                track_obstacle_changes,
+                activate_inputs_near_obstacles.run_if(resource_equals(NavMeshBakeState::Done)),
+                listen_for_footsteps,
+                query_mesh,
+                apply_steering,
+                traverse_navlinks,
+            )
+                .chain()
                 .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            (read_navmesh, start_navmesh_bake, load_or_generate_navmesh)
+                .chain()
                 .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            read_navlinks
+                .after(TransformSystem::TransformPropagate)
+                .in_base_set(CoreSet::PostUpdate),
         );
+
+    #[cfg(feature = "dev")]
+    app.add_system(bake_generated_navmesh.in_set(OnUpdate(GameState::Playing)));
 }
 
 #[derive(Debug, Component, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
 pub(crate) struct Follower;
 
+/// The route a [`Follower`] is currently following, kept around between frames so that it only needs to be re-planned
+/// when it is outdated.
+#[derive(Debug, Component, Clone, PartialEq, Default)]
+pub(crate) struct FollowerPath {
+    route: Option<Route>,
+    target: Vec3,
+    seconds_since_planned: f32,
+}
+
+impl FollowerPath {
+    fn needs_replan(&self, target: Vec3) -> bool {
+        self.route.is_none()
+            || self.seconds_since_planned > REPLAN_INTERVAL_SECONDS
+            || self.target.distance_squared(target) > REPLAN_TARGET_DISTANCE.squared()
+    }
+}
+
 #[sysfail(log(level = "error"))]
 fn query_mesh(
-    mut with_follower: Query<(&Transform, &mut Walking), (With<Follower>, Without<Player>)>,
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    mut with_follower: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Steering,
+            &mut FollowerPath,
+            Option<&Hearing>,
+        ),
+        (With<Follower>, Without<Player>, Without<NavLinkTraversal>),
+    >,
     with_player: Query<&Transform, (With<Player>, Without<Follower>)>,
+    nav_links: Query<&NavLink>,
+    mut obstacle_changes: EventReader<NavMeshObstacleChanged>,
     nav_mesh_settings: Res<NavMeshSettings>,
     nav_mesh: Res<NavMesh>,
     #[cfg(feature = "dev")] mut lines: ResMut<DebugLines>,
@@ -62,38 +157,76 @@ fn query_mesh(
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("query_mesh").entered();
+    let obstacle_changes: Vec<_> = obstacle_changes.iter().copied().collect();
     if let Ok(nav_mesh) = nav_mesh.get().read() {
-        for (follower_transform, mut walking) in &mut with_follower {
-            for player_transform in &with_player {
+        for (follower_entity, follower_transform, mut steering, mut follower_path, hearing) in
+            &mut with_follower
+        {
+            follower_path.seconds_since_planned += fixed_time.period.as_secs_f32();
+            if let Some(route) = &follower_path.route
+                && obstacle_changes.iter().any(|change| change.is_crossed_by(&route.path))
+            {
+                follower_path.route = None;
+            }
+            let targets: Vec<_> = match hearing {
+                Some(hearing) => hearing.last_heard_position.into_iter().collect(),
+                None => with_player
+                    .iter()
+                    .map(|transform| transform.translation)
+                    .collect(),
+            };
+            for to in targets {
                 let from = follower_transform.translation;
-                let to = player_transform.translation;
                 if (to - from).length_squared() < 3.0f32.squared() {
                     continue;
                 }
 
-                if let Ok(path) = find_path(&nav_mesh, &nav_mesh_settings, from, to, None, None) {
-                    let path = perform_string_pulling_on_path(&nav_mesh, from, to, &path)
-                        .map_err(|e| anyhow::Error::msg(format!("{e:?}")))?;
-                    #[cfg(feature = "dev")]
-                    if editor_state
-                        .window_state::<DevEditorWindow>()
-                        .context("Failed to get dev window state")?
-                        .navmesh_render_enabled
-                    {
-                        draw_path(&path, &mut lines, Color::RED);
+                if follower_path.needs_replan(to) {
+                    let route =
+                        find_route(&nav_mesh, &nav_mesh_settings, from, to, nav_links.iter())?;
+                    *follower_path = FollowerPath {
+                        route,
+                        target: to,
+                        seconds_since_planned: 0.0,
+                    };
+                }
+                let Some(route) = follower_path.route.as_mut() else {
+                    continue;
+                };
+                #[cfg(feature = "dev")]
+                if editor_state
+                    .window_state::<DevEditorWindow>()
+                    .context("Failed to get dev window state")?
+                    .navmesh_render_enabled
+                {
+                    draw_path(&route.path, &mut lines, Color::RED);
+                }
+                let up = follower_transform.up();
+                if let Some(link) = route.link && link.can_be_entered_from(from, up) {
+                    if let Some(mut entity_commands) = commands.get_entity(follower_entity) {
+                        entity_commands.insert(link.start_traversal());
                     }
-                    let dir = path
-                        .into_iter()
-                        .map(|next_point| {
-                            (next_point - from)
-                                .split(follower_transform.up())
-                                .horizontal
-                        })
-                        .filter(|dir| dir.length_squared() > 1e-3f32.squared())
-                        .filter_map(|dir| dir.try_normalize())
-                        .next();
-                    walking.direction = dir;
+                    // We will be somewhere else entirely after the traversal
+                    follower_path.route = None;
+                    continue;
                 }
+                let reached_waypoints = route
+                    .path
+                    .iter()
+                    .take_while(|waypoint| {
+                        (**waypoint - from).split(up).horizontal.length_squared()
+                            < WAYPOINT_RADIUS.squared()
+                    })
+                    .count();
+                route.path.drain(..reached_waypoints);
+                let dir = route
+                    .path
+                    .iter()
+                    .map(|next_point| (*next_point - from).split(up).horizontal)
+                    .filter(|dir| dir.length_squared() > 1e-3f32.squared())
+                    .filter_map(|dir| dir.try_normalize())
+                    .next();
+                steering.desired_direction = dir;
             }
         }
     }
@@ -0,0 +1,61 @@
+use crate::movement::general_movement::{Crouching, Grounded};
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Horizontal speed in m/s below which a character's footsteps are inaudible
+const MIN_AUDIBLE_SPEED: f32 = 0.5;
+
+/// Lets an NPC notice the [`Player`] by their footsteps instead of always knowing where they are.
+/// A [`Follower`](crate::movement::navigation::Follower) with hearing walks to where it last heard the player.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Hearing {
+    /// Distance in m at which footsteps of full loudness can still be heard.
+    /// Quieter footsteps, e.g. while crouching, have to be closer by the same factor.
+    pub(crate) range: f32,
+    pub(crate) last_heard_position: Option<Vec3>,
+}
+
+impl Default for Hearing {
+    fn default() -> Self {
+        Self {
+            range: 15.0,
+            last_heard_position: None,
+        }
+    }
+}
+
+/// Uses the same loudness as the footstep sounds, but is derived from the simulation state
+/// so that what NPCs hear does not depend on the frame rate.
+pub(crate) fn listen_for_footsteps(
+    mut listeners: Query<(&Transform, &mut Hearing), Without<Player>>,
+    players: Query<(&Transform, &Velocity, &Grounded, Option<&Crouching>), With<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("listen_for_footsteps").entered();
+    for (player_transform, velocity, grounded, crouching) in &players {
+        if !grounded.is_grounded() {
+            continue;
+        }
+        let horizontal_speed = grounded
+            .get_relative_velocity(velocity.linvel)
+            .split(player_transform.up())
+            .horizontal
+            .length();
+        if horizontal_speed < MIN_AUDIBLE_SPEED {
+            continue;
+        }
+        let loudness = crouching.map_or(1.0, |crouching| crouching.get_noise_factor());
+        for (listener_transform, mut hearing) in &mut listeners {
+            let distance_squared = listener_transform
+                .translation
+                .distance_squared(player_transform.translation);
+            if distance_squared < (hearing.range * loudness).squared() {
+                hearing.last_heard_position = Some(player_transform.translation);
+            }
+        }
+    }
+}
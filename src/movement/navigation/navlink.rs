@@ -0,0 +1,275 @@
+use crate::movement::general_movement::{Climbing, Grounded, Jumping, Walking};
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use oxidized_navigation::query::{find_path, perform_string_pulling_on_path};
+use oxidized_navigation::tiles::NavMeshTiles;
+use oxidized_navigation::NavMeshSettings;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// How close an agent needs to get to the entry of a link in order to start traversing it.
+const ENTRY_RADIUS: f32 = 0.5;
+/// How close an agent needs to get to the exit of a link to be considered done traversing it.
+const EXIT_RADIUS: f32 = 0.4;
+/// Traversals that take longer than this are aborted, e.g. when an agent got knocked off a ladder.
+const MAX_TRAVERSAL_SECONDS: f32 = 5.0;
+
+/// An off-mesh connection between two points on the navmesh that cannot be walked, like a ledge drop, a jump gap or a ladder.
+/// Authored in the level by tagging an object with `[navlink:jump]`, `[navlink:drop]` or `[navlink:climb]`.
+/// The object marks the entry of the link and its child tagged with `[navlink_end]` (or simply its first child) marks the exit.
+/// Links are one-way unless the object is additionally tagged with `[bidirectional]`.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct NavLink {
+    pub(crate) kind: NavLinkKind,
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) bidirectional: bool,
+}
+
+impl NavLink {
+    fn directions(&self) -> impl Iterator<Item = (Vec3, Vec3)> {
+        let forward = Some((self.start, self.end));
+        let backward = self.bidirectional.then_some((self.end, self.start));
+        forward.into_iter().chain(backward)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) enum NavLinkKind {
+    #[default]
+    Jump,
+    Drop,
+    Climb,
+}
+
+/// Present on an agent while it is moving through a [`NavLink`] instead of following the navmesh.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct NavLinkTraversal {
+    pub(crate) kind: NavLinkKind,
+    pub(crate) entry: Vec3,
+    pub(crate) exit: Vec3,
+    pub(crate) elapsed_seconds: f32,
+    pub(crate) has_left_ground: bool,
+}
+
+/// The best way to get somewhere, possibly using a [`NavLink`] on the way.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Route {
+    /// String-pulled path to the goal, or to the entry of [`Route::link`] if there is one.
+    pub(crate) path: Vec<Vec3>,
+    pub(crate) link: Option<PlannedLink>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PlannedLink {
+    pub(crate) kind: NavLinkKind,
+    pub(crate) entry: Vec3,
+    pub(crate) exit: Vec3,
+}
+
+impl PlannedLink {
+    pub(crate) fn can_be_entered_from(&self, position: Vec3, up: Vec3) -> bool {
+        let offset = (self.entry - position).split(up);
+        offset.horizontal.length_squared() < ENTRY_RADIUS.squared()
+            && offset.vertical.length_squared() < 1.0
+    }
+
+    pub(crate) fn start_traversal(&self) -> NavLinkTraversal {
+        NavLinkTraversal {
+            kind: self.kind,
+            entry: self.entry,
+            exit: self.exit,
+            ..default()
+        }
+    }
+}
+
+/// Finds the shortest route from `from` to `to`, either directly over the navmesh or through a single [`NavLink`].
+/// Chains of multiple links are not considered, since every candidate link costs two path queries.
+pub(crate) fn find_route<'a>(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    from: Vec3,
+    to: Vec3,
+    links: impl Iterator<Item = &'a NavLink>,
+) -> Result<Option<Route>> {
+    let direct = find_string_pulled_path(nav_mesh, nav_mesh_settings, from, to)?.map(|path| {
+        let cost = get_path_length(&path);
+        (Route { path, link: None }, cost)
+    });
+
+    let mut best = direct;
+    for link in links {
+        for (entry, exit) in link.directions() {
+            let Some(path_to_link) =
+                find_string_pulled_path(nav_mesh, nav_mesh_settings, from, entry)?
+            else {
+                continue;
+            };
+            let Some(path_from_link) =
+                find_string_pulled_path(nav_mesh, nav_mesh_settings, exit, to)?
+            else {
+                continue;
+            };
+            let cost = get_path_length(&path_to_link)
+                + entry.distance(exit)
+                + get_path_length(&path_from_link);
+            if best
+                .as_ref()
+                .map_or(true, |(_, best_cost)| cost < *best_cost)
+            {
+                let route = Route {
+                    path: path_to_link,
+                    link: Some(PlannedLink {
+                        kind: link.kind,
+                        entry,
+                        exit,
+                    }),
+                };
+                best = Some((route, cost));
+            }
+        }
+    }
+    Ok(best.map(|(route, _cost)| route))
+}
+
+fn find_string_pulled_path(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    from: Vec3,
+    to: Vec3,
+) -> Result<Option<Vec<Vec3>>> {
+    let Ok(path) = find_path(nav_mesh, nav_mesh_settings, from, to, None, None) else {
+        return Ok(None);
+    };
+    let path = perform_string_pulling_on_path(nav_mesh, from, to, &path)
+        .map_err(|e| anyhow::Error::msg(format!("{e:?}")))?;
+    Ok(Some(path))
+}
+
+fn get_path_length(path: &[Vec3]) -> f32 {
+    path.iter()
+        .zip(path.iter().skip(1))
+        .map(|(a, b)| a.distance(*b))
+        .sum()
+}
+
+static NAVLINK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[navlink:\s*(jump|drop|climb)\]").expect("Failed to compile navlink regex")
+});
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn read_navlinks(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name, &GlobalTransform, &Children), Added<Name>>,
+    names: Query<&Name>,
+    global_transforms: Query<&GlobalTransform>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_navlinks").entered();
+    for (entity, name, global_transform, children) in &added_name {
+        let name = name.to_lowercase();
+        let Some(captures) = NAVLINK_REGEX.captures(&name) else {
+            continue;
+        };
+        let kind = match &captures[1] {
+            "jump" => NavLinkKind::Jump,
+            "drop" => NavLinkKind::Drop,
+            "climb" => NavLinkKind::Climb,
+            // Guaranteed by the regex
+            _ => unreachable!(),
+        };
+        let end_entity = children
+            .iter()
+            .find(|child| {
+                names
+                    .get(**child)
+                    .map(|name| name.to_lowercase().contains("[navlink_end]"))
+                    .unwrap_or_default()
+            })
+            .or_else(|| children.first())
+            .with_context(|| format!("Navlink \"{name}\" has no child marking its end"))?;
+        let end = global_transforms
+            .get(*end_entity)
+            .context("Failed to get global transform of navlink end")?
+            .translation();
+
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(NavLink {
+                kind,
+                start: global_transform.translation(),
+                end,
+                bidirectional: name.contains("[bidirectional]"),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn traverse_navlinks(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    mut agents: Query<(
+        Entity,
+        &Transform,
+        &Grounded,
+        &mut NavLinkTraversal,
+        &mut Walking,
+        &mut Jumping,
+        Option<&mut Climbing>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("traverse_navlinks").entered();
+    for (entity, transform, grounded, mut traversal, mut walking, mut jumping, climbing) in
+        &mut agents
+    {
+        traversal.elapsed_seconds += fixed_time.period.as_secs_f32();
+        traversal.has_left_ground |= !grounded.is_grounded();
+        let up = transform.up();
+        let to_exit = traversal.exit - transform.translation;
+        let horizontal_to_exit = to_exit.split(up).horizontal;
+        let reached_exit = to_exit.length_squared() < EXIT_RADIUS.squared();
+
+        let is_done = match traversal.kind {
+            NavLinkKind::Jump | NavLinkKind::Drop => {
+                let has_landed = traversal.has_left_ground && grounded.is_grounded();
+                walking.direction = horizontal_to_exit.try_normalize();
+                jumping.requested |=
+                    traversal.kind == NavLinkKind::Jump && !traversal.has_left_ground;
+                has_landed || reached_exit
+            }
+            NavLinkKind::Climb => {
+                // Climb straight up or down first and only step off once we are level with the exit
+                let direction = if to_exit.dot(up).abs() > EXIT_RADIUS {
+                    up * to_exit.dot(up).signum()
+                } else {
+                    horizontal_to_exit.normalize_or_zero()
+                };
+                if let Some(mut climbing) = climbing {
+                    climbing.direction = Some(direction);
+                } else if let Some(mut entity_commands) = commands.get_entity(entity) {
+                    entity_commands.insert(Climbing {
+                        direction: Some(direction),
+                        ..default()
+                    });
+                }
+                reached_exit
+            }
+        };
+
+        if is_done || traversal.elapsed_seconds > MAX_TRAVERSAL_SECONDS {
+            if let Some(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.remove::<(NavLinkTraversal, Climbing)>();
+            }
+        }
+    }
+}
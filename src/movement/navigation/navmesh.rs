@@ -1,44 +1,42 @@
+use crate::movement::navigation::navmesh_cache::NavMeshInput;
 use crate::util::trait_extension::MeshExt;
+use anyhow::{Context, Result};
 use bevy::prelude::*;
-use bevy_pathmesh::PathMesh;
-#[cfg(feature = "dev")]
-use serde::{Deserialize, Serialize};
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
 
+/// Turns meshes tagged with `[navmesh]` into hand-authored navmesh input, so that level designers can add walkable area
+/// the automatic generation misses. Tagging a mesh with `[navmesh:exclusive]` instead makes the authored meshes
+/// the only walkable area of the level, overriding the automatic generation entirely.
+/// The meshes become invisible sensor colliders, so they neither render nor block anything.
+#[sysfail(log(level = "error"))]
 pub(crate) fn read_navmesh(
     mut commands: Commands,
-    added_name: Query<(Entity, &Name, &GlobalTransform), Added<Name>>,
+    added_name: Query<(Entity, &Name), Added<Name>>,
     children: Query<&Children>,
     meshes: Res<Assets<Mesh>>,
     mesh_handles: Query<&Handle<Mesh>>,
-    mut path_meshes: ResMut<Assets<PathMesh>>,
-) {
+) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("read_navmesh").entered();
-    for (parent, name, global_transform) in &added_name {
-        if name.to_lowercase().contains("[navmesh]") {
-            let transform = global_transform.compute_transform();
-            for (_child, mesh) in
-                Mesh::search_in_children(parent, &children, &meshes, &mesh_handles)
-            {
-                let mesh = mesh.transformed(transform);
-
-                let path_mesh = PathMesh::from_bevy_mesh_and_then(&mesh, |mesh| {
-                    mesh.set_delta(5.);
-                });
-                
-                if let Some(entity_commands) = commands.get_entity(parent) {
-                    entity_commands.insert((
-                        path_meshes.add(path_mesh),
-                        #[cfg(feature = "dev")]
-                        NavMesh,
-                    ));
-                }
+    for (parent, name) in &added_name {
+        let name = name.to_lowercase();
+        let exclusive = name.contains("[navmesh:exclusive]");
+        if !exclusive && !name.contains("[navmesh]") {
+            continue;
+        }
+        for (child, mesh) in Mesh::search_in_children(parent, &children, &meshes, &mesh_handles) {
+            let collider = Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh)
+                .context("Failed to create navmesh collider from mesh")?;
+            if let Some(mut entity_commands) = commands.get_entity(child) {
+                entity_commands.insert((
+                    collider,
+                    Sensor,
+                    NavMeshInput::Authored { exclusive },
+                    Visibility::Hidden,
+                ));
             }
         }
     }
+    Ok(())
 }
-
-#[cfg(feature = "dev")]
-#[derive(Debug, Component, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
-#[reflect(Component, Serialize, Deserialize)]
-pub(crate) struct NavMesh;
@@ -0,0 +1,582 @@
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::movement::navigation::obstacle::{
+    find_inputs_in_affected_tiles, get_obstacle_bounds, NavMeshObstacle,
+};
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::parry::shape::{SharedShape, TypedShape};
+use bevy_rapier3d::prelude::*;
+use oxidized_navigation::tiles::{
+    EdgeConnection, EdgeConnectionDirection, Link, NavMeshTile, Polygon,
+};
+use oxidized_navigation::{NavMesh, NavMeshAffector, NavMeshSettings};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How long no new colliders need to have been spawned before a level is considered fully loaded.
+const LEVEL_SETTLE_SECONDS: f32 = 0.5;
+/// How long the generated tiles need to stay unchanged before runtime generation is considered done.
+#[cfg(feature = "dev")]
+const GENERATION_SETTLE_SECONDS: f32 = 1.0;
+
+/// Marks a collider that should contribute to the navmesh.
+/// Only turned into a [`NavMeshAffector`] when no up-to-date baked navmesh exists for the current level,
+/// so that [`oxidized_navigation`] does not regenerate tiles we already loaded from disk.
+#[derive(
+    Debug, Component, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) enum NavMeshInput {
+    /// Regular level geometry tagged with `[collider]`
+    #[default]
+    Collider,
+    /// Walkable area drawn by a level designer, see [`read_navmesh`](super::navmesh::read_navmesh).
+    /// If any authored input in a level is `exclusive`, only authored inputs are used for that level.
+    Authored { exclusive: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub(crate) enum NavMeshBakeState {
+    /// No level has been loaded yet.
+    #[default]
+    Idle,
+    /// The level is still spawning its colliders.
+    Settling {
+        level: String,
+        quiet_seconds: f32,
+    },
+    /// Tiles are being generated at runtime because the cache was missing or stale.
+    Generating {
+        level: String,
+        hash: u64,
+        quiet_seconds: f32,
+        last_fingerprint: usize,
+    },
+    Done,
+}
+
+/// The tiles of a navmesh as written to `assets/levels/<level>.navmesh.ron`.
+/// Mirrors [`NavMeshTiles`] because its types are not serializable themselves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BakedNavMesh {
+    /// Hash of all [`NavMeshInput`] collider geometry, excluding any [`NavMeshObstacle`], and the [`NavMeshSettings`] the tiles were generated with.
+    /// When it does not match the current level, the bake is stale and ignored.
+    pub(crate) hash: u64,
+    pub(crate) tiles: Vec<BakedTile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BakedTile {
+    pub(crate) coord: UVec2,
+    pub(crate) vertices: Vec<Vec3>,
+    pub(crate) edges: Vec<[BakedEdgeConnection; 3]>,
+    pub(crate) polygons: Vec<BakedPolygon>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BakedPolygon {
+    pub(crate) indices: [u32; 3],
+    pub(crate) links: Vec<BakedLink>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum BakedEdgeConnection {
+    None,
+    Internal(u16),
+    External(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum BakedLink {
+    Internal {
+        edge: u8,
+        neighbour_polygon: u16,
+    },
+    OffMesh {
+        edge: u8,
+        neighbour_polygon: u16,
+        direction: BakedDirection,
+        bound_min: u8,
+        bound_max: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum BakedDirection {
+    XNegative,
+    ZPositive,
+    XPositive,
+    ZNegative,
+}
+
+pub(crate) fn start_navmesh_bake(
+    current_level: Option<Res<CurrentLevel>>,
+    mut bake_state: ResMut<NavMeshBakeState>,
+    nav_mesh: Res<NavMesh>,
+) {
+    let Some(current_level) = current_level else {
+        return;
+    };
+    if !current_level.is_changed() {
+        return;
+    }
+    // Tiles loaded from a cache are not owned by any affector, so they would otherwise survive a level change
+    if let Ok(mut nav_mesh) = nav_mesh.get().write() {
+        nav_mesh.tiles.clear();
+        nav_mesh.tile_generations.clear();
+    }
+    *bake_state = NavMeshBakeState::Settling {
+        level: current_level.scene.clone(),
+        quiet_seconds: 0.0,
+    };
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn load_or_generate_navmesh(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bake_state: ResMut<NavMeshBakeState>,
+    added_inputs: Query<(), Added<NavMeshInput>>,
+    inputs: Query<(Entity, &NavMeshInput, &Collider, &GlobalTransform)>,
+    obstacles: Query<(&Collider, &GlobalTransform), With<NavMeshObstacle>>,
+    inactive_inputs: Query<(), (With<NavMeshInput>, Without<NavMeshAffector>)>,
+    rapier_context: Res<RapierContext>,
+    nav_mesh_settings: Res<NavMeshSettings>,
+    nav_mesh: Res<NavMesh>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_or_generate_navmesh").entered();
+    let NavMeshBakeState::Settling {
+        level,
+        quiet_seconds,
+    } = bake_state.as_mut()
+    else {
+        return Ok(());
+    };
+    if !added_inputs.is_empty() {
+        *quiet_seconds = 0.0;
+        return Ok(());
+    }
+    *quiet_seconds += time.delta_seconds();
+    if *quiet_seconds < LEVEL_SETTLE_SECONDS || inputs.is_empty() {
+        return Ok(());
+    }
+
+    let level = level.clone();
+    let only_authored = inputs
+        .iter()
+        .any(|(_entity, input, _collider, _transform)| {
+            matches!(input, NavMeshInput::Authored { exclusive: true })
+        });
+    let (inputs, ignored_inputs): (Vec<_>, Vec<_>) =
+        inputs
+            .iter()
+            .partition(|(_entity, input, _collider, _transform)| {
+                !only_authored || matches!(input, NavMeshInput::Authored { .. })
+            });
+    for (entity, _input, _collider, _transform) in ignored_inputs {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<NavMeshInput>();
+        }
+    }
+    let hash = hash_navmesh_inputs(
+        inputs
+            .iter()
+            .map(|(_entity, _input, collider, transform)| (*collider, *transform)),
+        &nav_mesh_settings,
+    );
+    match read_baked_navmesh(&level) {
+        Ok(baked) if baked.hash == hash => {
+            let mut nav_mesh = nav_mesh
+                .get()
+                .write()
+                .map_err(|e| anyhow::Error::msg(format!("Failed to lock navmesh: {e}")))?;
+            for tile in baked.tiles {
+                nav_mesh.tile_generations.insert(tile.coord, 0);
+                nav_mesh.tiles.insert(tile.coord, tile.into());
+            }
+            // Obstacles are not part of the bake, so the tiles around them need to be generated at runtime anyways
+            for (collider, transform) in &obstacles {
+                let bounds = get_obstacle_bounds(collider, transform);
+                for entity in find_inputs_in_affected_tiles(
+                    &bounds,
+                    &nav_mesh_settings,
+                    &inactive_inputs,
+                    &rapier_context,
+                ) {
+                    if let Some(mut entity_commands) = commands.get_entity(entity) {
+                        entity_commands.insert(NavMeshAffector);
+                    }
+                }
+            }
+            info!("Loaded baked navmesh for level \"{level}\"");
+            *bake_state = NavMeshBakeState::Done;
+        }
+        result => {
+            match result {
+                Ok(_) => {
+                    info!("Baked navmesh for level \"{level}\" is stale, generating it at runtime")
+                }
+                Err(e) => {
+                    info!("No baked navmesh for level \"{level}\", generating it at runtime: {e:#}")
+                }
+            }
+            for (entity, _input, _collider, _transform) in inputs {
+                if let Some(mut entity_commands) = commands.get_entity(entity) {
+                    entity_commands.insert(NavMeshAffector);
+                }
+            }
+            *bake_state = NavMeshBakeState::Generating {
+                level,
+                hash,
+                quiet_seconds: 0.0,
+                last_fingerprint: 0,
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Writes the navmesh to disk once runtime generation has settled down so that the next load of the level can skip it.
+#[cfg(feature = "dev")]
+#[sysfail(log(level = "error"))]
+pub(crate) fn bake_generated_navmesh(
+    time: Res<Time>,
+    mut bake_state: ResMut<NavMeshBakeState>,
+    nav_mesh: Res<NavMesh>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("bake_generated_navmesh").entered();
+    let NavMeshBakeState::Generating {
+        level,
+        hash,
+        quiet_seconds,
+        last_fingerprint,
+    } = bake_state.as_mut()
+    else {
+        return Ok(());
+    };
+    let Ok(tiles) = nav_mesh.get().read() else {
+        return Ok(());
+    };
+    let fingerprint = tiles
+        .get_tiles()
+        .values()
+        .map(|tile| tile.vertices.len() + tile.polygons.len())
+        .sum();
+    if fingerprint == 0 || fingerprint != *last_fingerprint {
+        *last_fingerprint = fingerprint;
+        *quiet_seconds = 0.0;
+        return Ok(());
+    }
+    *quiet_seconds += time.delta_seconds();
+    if *quiet_seconds < GENERATION_SETTLE_SECONDS {
+        return Ok(());
+    }
+
+    let baked = BakedNavMesh {
+        hash: *hash,
+        tiles: tiles
+            .get_tiles()
+            .iter()
+            .map(|(coord, tile)| BakedTile::from_tile(*coord, tile))
+            .collect(),
+    };
+    let path = get_baked_navmesh_path(level);
+    let dir = path.parent().context("Failed to get navmesh directory")?;
+    fs::create_dir_all(dir).context("Failed to create navmesh directory")?;
+    let serialized = ron::ser::to_string_pretty(&baked, default())
+        .context("Failed to serialize baked navmesh")?;
+    fs::write(&path, serialized).context("Failed to write baked navmesh")?;
+    info!(
+        "Successfully baked navmesh for level \"{}\" at {}",
+        level,
+        path.to_string_lossy()
+    );
+    *bake_state = NavMeshBakeState::Done;
+    Ok(())
+}
+
+fn get_baked_navmesh_path(level: &str) -> PathBuf {
+    Path::new("assets")
+        .join("levels")
+        .join(level)
+        .with_extension("navmesh.ron")
+}
+
+fn read_baked_navmesh(level: &str) -> Result<BakedNavMesh> {
+    let path = get_baked_navmesh_path(level);
+    let serialized = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    ron::from_str(&serialized).context("Failed to deserialize baked navmesh")
+}
+
+fn hash_navmesh_inputs<'a>(
+    inputs: impl Iterator<Item = (&'a Collider, &'a GlobalTransform)>,
+    settings: &NavMeshSettings,
+) -> u64 {
+    // Spawn order is not deterministic, so every collider is hashed on its own and the results are sorted
+    let mut collider_hashes: Vec<_> = inputs
+        .map(|(collider, transform)| {
+            let mut hasher = StableHasher::new();
+            hasher.write_f32s(transform.compute_matrix().to_cols_array());
+            hash_shape(&mut hasher, &collider.raw);
+            hasher.finish()
+        })
+        .collect();
+    collider_hashes.sort_unstable();
+
+    let mut hasher = StableHasher::new();
+    hasher.write_u64s(collider_hashes);
+    hasher.write_f32s([
+        settings.cell_width,
+        settings.cell_height,
+        settings.world_half_extents,
+        settings.world_bottom_bound,
+        settings.max_traversable_slope_radians,
+        settings.max_contour_simplification_error,
+    ]);
+    hasher.write_u64s([
+        settings.tile_width as u64,
+        settings.walkable_height as u64,
+        settings.walkable_radius as u64,
+        settings.step_height as u64,
+        settings.min_region_area as u64,
+        settings.merge_region_area as u64,
+        settings.max_edge_length as u64,
+    ]);
+    hasher.finish()
+}
+
+/// Hashes the type of the shape and everything that determines its geometry
+fn hash_shape(hasher: &mut StableHasher, shape: &SharedShape) {
+    hasher.write_u64s([shape.shape_type() as u64]);
+    match shape.as_typed_shape() {
+        TypedShape::Ball(ball) => hasher.write_f32s([ball.radius]),
+        TypedShape::Cuboid(cuboid) => hasher.write_f32s(cuboid.half_extents.iter().copied()),
+        TypedShape::Capsule(capsule) => hasher.write_f32s(
+            capsule
+                .segment
+                .a
+                .iter()
+                .chain(capsule.segment.b.iter())
+                .copied()
+                .chain([capsule.radius]),
+        ),
+        TypedShape::Cylinder(cylinder) => {
+            hasher.write_f32s([cylinder.half_height, cylinder.radius]);
+        }
+        TypedShape::Cone(cone) => hasher.write_f32s([cone.half_height, cone.radius]),
+        TypedShape::TriMesh(trimesh) => {
+            hasher.write_f32s(
+                trimesh
+                    .vertices()
+                    .iter()
+                    .flat_map(|vertex| vertex.iter().copied()),
+            );
+            hasher.write_u64s(
+                trimesh
+                    .indices()
+                    .iter()
+                    .flatten()
+                    .map(|index| *index as u64),
+            );
+        }
+        TypedShape::ConvexPolyhedron(polyhedron) => {
+            hasher.write_f32s(
+                polyhedron
+                    .points()
+                    .iter()
+                    .flat_map(|point| point.iter().copied()),
+            );
+        }
+        TypedShape::Compound(compound) => {
+            for (isometry, shape) in compound.shapes() {
+                hasher.write_f32s(isometry.to_homogeneous().iter().copied());
+                hash_shape(hasher, shape);
+            }
+        }
+        _ => {
+            // Not used for level geometry, so the bounding box is good enough to notice changes
+            let aabb = shape.compute_local_aabb();
+            hasher.write_f32s(aabb.mins.iter().chain(aabb.maxs.iter()).copied());
+        }
+    }
+}
+
+/// 64 bit FNV-1a. Baked navmeshes are committed along with their hash, so unlike [`std::collections::hash_map::DefaultHasher`],
+/// the result must not change between Rust releases or platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u64s(&mut self, values: impl IntoIterator<Item = u64>) {
+        for value in values {
+            self.write(&value.to_le_bytes());
+        }
+    }
+
+    fn write_f32s(&mut self, values: impl IntoIterator<Item = f32>) {
+        for value in values {
+            self.write(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+impl BakedTile {
+    fn from_tile(coord: UVec2, tile: &NavMeshTile) -> Self {
+        Self {
+            coord,
+            vertices: tile.vertices.clone(),
+            edges: tile
+                .edges
+                .iter()
+                .map(|edges| edges.map(BakedEdgeConnection::from))
+                .collect(),
+            polygons: tile
+                .polygons
+                .iter()
+                .map(|polygon| BakedPolygon {
+                    indices: polygon.indices,
+                    links: polygon.links.iter().copied().map(BakedLink::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<BakedTile> for NavMeshTile {
+    fn from(tile: BakedTile) -> Self {
+        Self {
+            vertices: tile.vertices,
+            edges: tile
+                .edges
+                .into_iter()
+                .map(|edges| edges.map(EdgeConnection::from))
+                .collect(),
+            polygons: tile
+                .polygons
+                .into_iter()
+                .map(|polygon| Polygon {
+                    indices: polygon.indices,
+                    links: polygon.links.into_iter().map(Link::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<EdgeConnection> for BakedEdgeConnection {
+    fn from(connection: EdgeConnection) -> Self {
+        match connection {
+            EdgeConnection::None => Self::None,
+            EdgeConnection::Internal(polygon) => Self::Internal(polygon),
+            EdgeConnection::External(edge) => Self::External(edge),
+        }
+    }
+}
+
+impl From<BakedEdgeConnection> for EdgeConnection {
+    fn from(connection: BakedEdgeConnection) -> Self {
+        match connection {
+            BakedEdgeConnection::None => Self::None,
+            BakedEdgeConnection::Internal(polygon) => Self::Internal(polygon),
+            BakedEdgeConnection::External(edge) => Self::External(edge),
+        }
+    }
+}
+
+impl From<Link> for BakedLink {
+    fn from(link: Link) -> Self {
+        match link {
+            Link::Internal {
+                edge,
+                neighbour_polygon,
+            } => Self::Internal {
+                edge,
+                neighbour_polygon,
+            },
+            Link::OffMesh {
+                edge,
+                neighbour_polygon,
+                direction,
+                bound_min,
+                bound_max,
+            } => Self::OffMesh {
+                edge,
+                neighbour_polygon,
+                direction: direction.into(),
+                bound_min,
+                bound_max,
+            },
+        }
+    }
+}
+
+impl From<BakedLink> for Link {
+    fn from(link: BakedLink) -> Self {
+        match link {
+            BakedLink::Internal {
+                edge,
+                neighbour_polygon,
+            } => Self::Internal {
+                edge,
+                neighbour_polygon,
+            },
+            BakedLink::OffMesh {
+                edge,
+                neighbour_polygon,
+                direction,
+                bound_min,
+                bound_max,
+            } => Self::OffMesh {
+                edge,
+                neighbour_polygon,
+                direction: direction.into(),
+                bound_min,
+                bound_max,
+            },
+        }
+    }
+}
+
+impl From<EdgeConnectionDirection> for BakedDirection {
+    fn from(direction: EdgeConnectionDirection) -> Self {
+        match direction {
+            EdgeConnectionDirection::XNegative => Self::XNegative,
+            EdgeConnectionDirection::ZPositive => Self::ZPositive,
+            EdgeConnectionDirection::XPositive => Self::XPositive,
+            EdgeConnectionDirection::ZNegative => Self::ZNegative,
+        }
+    }
+}
+
+impl From<BakedDirection> for EdgeConnectionDirection {
+    fn from(direction: BakedDirection) -> Self {
+        match direction {
+            BakedDirection::XNegative => Self::XNegative,
+            BakedDirection::ZPositive => Self::ZPositive,
+            BakedDirection::XPositive => Self::XPositive,
+            BakedDirection::ZNegative => Self::ZNegative,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::movement::navigation::navmesh_cache::NavMeshInput;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use oxidized_navigation::{NavMeshAffector, NavMeshSettings};
+use serde::{Deserialize, Serialize};
+
+/// Extra space around an obstacle in which paths count as crossing it, so that e.g. a swinging door stays covered.
+const OBSTACLE_MARGIN: f32 = 2.0;
+
+/// A collider that can move or vanish at runtime, like a door or a destructible crate.
+/// Authored in the level by tagging a `[collider]` object with `[dynamic]`.
+/// Obstacles are always [`NavMeshAffector`]s, so [`oxidized_navigation`] regenerates the tiles they touch whenever they change.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct NavMeshObstacle;
+
+/// Sent whenever a [`NavMeshObstacle`] moves or is despawned. Covers both the old and the new location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct NavMeshObstacleChanged {
+    pub(crate) center: Vec3,
+    pub(crate) radius: f32,
+    /// World space bounding box of the collider
+    pub(crate) min: Vec3,
+    pub(crate) max: Vec3,
+}
+
+impl NavMeshObstacleChanged {
+    pub(crate) fn is_crossed_by(&self, path: &[Vec3]) -> bool {
+        path.iter()
+            .zip(path.iter().skip(1))
+            .any(|(a, b)| get_distance_to_segment(self.center, *a, *b) < self.radius)
+    }
+
+    /// The lowest and highest coordinates of the navmesh tiles the obstacle is part of.
+    /// Tiles are generated with a border of [`NavMeshSettings::walkable_radius`] cells around them,
+    /// so an obstacle close to the edge of a tile is also part of the neighboring tile.
+    pub(crate) fn get_affected_tiles(&self, settings: &NavMeshSettings) -> (UVec2, UVec2) {
+        let border = Vec2::splat(settings.walkable_radius as f32 * settings.cell_width);
+        let min = get_tile_containing(self.min.xz() - border, settings);
+        let max = get_tile_containing(self.max.xz() + border, settings);
+        (min, max)
+    }
+}
+
+/// Runs in the fixed timestep, so removals are detected by comparing against the obstacles that still exist,
+/// since [`RemovedComponents`] would miss those that happened in frames without a tick.
+pub(crate) fn track_obstacle_changes(
+    moved_obstacles: Query<
+        (Entity, &Collider, &GlobalTransform),
+        (With<NavMeshObstacle>, Changed<GlobalTransform>),
+    >,
+    obstacles: Query<(), With<NavMeshObstacle>>,
+    mut last_bounds: Local<HashMap<Entity, NavMeshObstacleChanged>>,
+    mut obstacle_changes: EventWriter<NavMeshObstacleChanged>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("track_obstacle_changes").entered();
+    for (entity, collider, transform) in &moved_obstacles {
+        let bounds = get_obstacle_bounds(collider, transform);
+        if let Some(old_bounds) = last_bounds.insert(entity, bounds) {
+            obstacle_changes.send(old_bounds);
+        }
+        obstacle_changes.send(bounds);
+    }
+    last_bounds.retain(|entity, old_bounds| {
+        let exists = obstacles.contains(*entity);
+        if !exists {
+            obstacle_changes.send(*old_bounds);
+        }
+        exists
+    });
+}
+
+/// Tiles loaded from a baked navmesh are only regenerated if every collider in them is a [`NavMeshAffector`],
+/// so the static geometry in the tiles of a changed obstacle needs to start affecting the navmesh as well.
+pub(crate) fn activate_inputs_near_obstacles(
+    mut commands: Commands,
+    mut obstacle_changes: EventReader<NavMeshObstacleChanged>,
+    inactive_inputs: Query<(), (With<NavMeshInput>, Without<NavMeshAffector>)>,
+    rapier_context: Res<RapierContext>,
+    nav_mesh_settings: Res<NavMeshSettings>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("activate_inputs_near_obstacles").entered();
+    for change in obstacle_changes.iter() {
+        for entity in find_inputs_in_affected_tiles(
+            change,
+            &nav_mesh_settings,
+            &inactive_inputs,
+            &rapier_context,
+        ) {
+            if let Some(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.insert(NavMeshAffector);
+            }
+        }
+    }
+}
+
+pub(crate) fn get_obstacle_bounds(
+    collider: &Collider,
+    transform: &GlobalTransform,
+) -> NavMeshObstacleChanged {
+    let aabb = collider.raw.compute_local_aabb();
+    let local_center = aabb.center();
+    let (scale, _rotation, _translation) = transform.to_scale_rotation_translation();
+    let (min, max) = aabb.vertices().iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), vertex| {
+            let vertex = transform.transform_point(Vec3::new(vertex.x, vertex.y, vertex.z));
+            (min.min(vertex), max.max(vertex))
+        },
+    );
+    NavMeshObstacleChanged {
+        center: transform.transform_point(Vec3::new(
+            local_center.x,
+            local_center.y,
+            local_center.z,
+        )),
+        radius: aabb.half_extents().norm() * scale.max_element() + OBSTACLE_MARGIN,
+        min,
+        max,
+    }
+}
+
+/// Finds all inputs that don't affect the navmesh yet and overlap one of the tiles the obstacle is part of.
+pub(crate) fn find_inputs_in_affected_tiles(
+    bounds: &NavMeshObstacleChanged,
+    settings: &NavMeshSettings,
+    inputs: &Query<(), (With<NavMeshInput>, Without<NavMeshAffector>)>,
+    rapier_context: &RapierContext,
+) -> Vec<Entity> {
+    let tile_size = get_tile_size(settings);
+    let (min_tile, max_tile) = bounds.get_affected_tiles(settings);
+    let min = min_tile.as_vec2() * tile_size - settings.world_half_extents;
+    let max = (max_tile + UVec2::ONE).as_vec2() * tile_size - settings.world_half_extents;
+    let half_extents = (max - min) / 2.;
+    let center = min + half_extents;
+    // Tiles reach all the way up, so treat the world as being as high as it is wide
+    let half_height = settings.world_half_extents;
+    let center = Vec3::new(
+        center.x,
+        settings.world_bottom_bound + half_height,
+        center.y,
+    );
+
+    let mut entities = Vec::new();
+    rapier_context.intersections_with_shape(
+        center,
+        Quat::IDENTITY,
+        &Collider::cuboid(half_extents.x, half_height, half_extents.y),
+        QueryFilter::only_fixed(),
+        |entity| {
+            if inputs.contains(entity) {
+                entities.push(entity);
+            }
+            true
+        },
+    );
+    entities
+}
+
+/// Size of a navmesh tile in m
+fn get_tile_size(settings: &NavMeshSettings) -> f32 {
+    settings.cell_width * settings.tile_width as f32
+}
+
+/// Same as the coordinates [`oxidized_navigation`] uses for its tiles, with the world starting at [`NavMeshSettings::world_half_extents`] in the negative
+fn get_tile_containing(position: Vec2, settings: &NavMeshSettings) -> UVec2 {
+    ((position + settings.world_half_extents) / get_tile_size(settings))
+        .max(Vec2::ZERO)
+        .as_uvec2()
+}
+
+fn get_distance_to_segment(point: Vec3, start: Vec3, end: Vec3) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_squared() > 0.0 {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(start + segment * t)
+}
@@ -0,0 +1,293 @@
+use crate::file_system_interaction::config::{GameConfig, Steering as SteeringConfig};
+use crate::level_instantiation::spawning::objects::player;
+use crate::movement::general_movement::Walking;
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// Sits between pathfinding and [`Walking::direction`].
+/// Pathfinding only writes [`Steering::desired_direction`], which is then blended with
+/// local avoidance so that groups of agents flow around each other and around the player instead of pushing each other.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Steering {
+    /// Direction the agent wants to go this tick as determined by pathfinding. Reset every tick.
+    pub(crate) desired_direction: Option<Vec3>,
+    /// Radius of the agent's footprint used for avoidance
+    pub(crate) radius: f32,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Self {
+            desired_direction: None,
+            radius: 0.4,
+        }
+    }
+}
+
+impl Steering {
+    pub(crate) fn with_radius(radius: f32) -> Self {
+        Self {
+            radius,
+            ..default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Neighbour {
+    position: Vec3,
+    velocity: Vec3,
+    radius: f32,
+    /// Whether the neighbour also runs avoidance and thus takes on half of the effort to avoid a collision
+    reciprocal: bool,
+}
+
+/// Minimum speed assumed when sampling candidate velocities, so that agents starting from a standstill still avoid each other.
+const MIN_PREFERRED_SPEED: f32 = 1.0;
+const CANDIDATE_ANGLE_COUNT: i32 = 6;
+const CANDIDATE_SPEED_FACTORS: [f32; 2] = [1.0, 0.5];
+
+pub(crate) fn apply_steering(
+    mut agents: Query<(Entity, &Transform, &Velocity, &mut Steering, &mut Walking)>,
+    players: Query<(Entity, &Transform, &Velocity), (With<Player>, Without<Steering>)>,
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_steering").entered();
+    let config = &config.steering;
+    // Snapshot everything we want to avoid first so that agents can read each other while being mutated
+    let avoidables: HashMap<_, _> = agents
+        .iter()
+        .map(|(entity, transform, velocity, steering, _walking)| {
+            let neighbour = Neighbour {
+                position: transform.translation,
+                velocity: velocity.linvel,
+                radius: steering.radius,
+                reciprocal: true,
+            };
+            (entity, neighbour)
+        })
+        .chain(players.iter().map(|(entity, transform, velocity)| {
+            let neighbour = Neighbour {
+                position: transform.translation,
+                velocity: velocity.linvel,
+                radius: player::RADIUS,
+                reciprocal: false,
+            };
+            (entity, neighbour)
+        }))
+        .collect();
+
+    for (entity, transform, velocity, mut steering, mut walking) in &mut agents {
+        let up = transform.up();
+        let position = transform.translation;
+        let neighbours: Vec<_> =
+            find_neighbours(entity, position, config.neighbour_radius, &rapier_context)
+                .into_iter()
+                .filter_map(|neighbour| avoidables.get(&neighbour))
+                .map(|neighbour| Neighbour {
+                    velocity: neighbour.velocity.split(up).horizontal,
+                    ..*neighbour
+                })
+                .collect();
+
+        let current_velocity = velocity.linvel.split(up).horizontal;
+        let seek = steering
+            .desired_direction
+            .take()
+            .map(|direction| direction.split(up).horizontal)
+            .filter(|direction| !direction.is_approx_zero())
+            .map(|direction| {
+                let speed = current_velocity.length().max(MIN_PREFERRED_SPEED);
+                let preferred_velocity = direction.normalize() * speed;
+                choose_velocity(
+                    position,
+                    current_velocity,
+                    preferred_velocity,
+                    steering.radius,
+                    &neighbours,
+                    up,
+                    config,
+                ) / speed
+            });
+
+        let separation = get_separation(
+            position,
+            steering.radius,
+            &neighbours,
+            up,
+            config.separation_distance,
+        );
+        let avoidance = seek
+            .map(|seek| get_obstacle_avoidance(entity, position, seek, up, &rapier_context, config))
+            .unwrap_or_default();
+
+        let direction = seek.unwrap_or_default()
+            + separation * config.separation_weight
+            + avoidance * config.obstacle_weight;
+        walking.direction = (!direction.is_approx_zero()).then(|| direction.clamp_length_max(1.0));
+    }
+}
+
+fn find_neighbours(
+    entity: Entity,
+    position: Vec3,
+    radius: f32,
+    rapier_context: &RapierContext,
+) -> Vec<Entity> {
+    let mut neighbours = Vec::new();
+    rapier_context.intersections_with_shape(
+        position,
+        Quat::IDENTITY,
+        &Collider::ball(radius),
+        QueryFilter::only_dynamic()
+            .exclude_collider(entity)
+            .exclude_sensors(),
+        |neighbour| {
+            neighbours.push(neighbour);
+            true
+        },
+    );
+    neighbours
+}
+
+/// Pushes the agent away from every neighbour that is closer than `separation_distance`, stronger the closer it is.
+fn get_separation(
+    position: Vec3,
+    radius: f32,
+    neighbours: &[Neighbour],
+    up: Vec3,
+    separation_distance: f32,
+) -> Vec3 {
+    neighbours
+        .iter()
+        .filter_map(|neighbour| {
+            let away = (position - neighbour.position).split(up).horizontal;
+            let gap = away.length() - radius - neighbour.radius;
+            if gap >= separation_distance {
+                return None;
+            }
+            let strength = 1.0 - (gap / separation_distance).max(0.0);
+            // Agents standing exactly on top of each other have no defined "away", so we pick one deterministically
+            let away = away
+                .try_normalize()
+                .unwrap_or_else(|| up.any_orthonormal_vector());
+            Some(away * strength)
+        })
+        .sum()
+}
+
+/// Samples candidate velocities around the preferred one and picks the one that best balances
+/// deviating from the preferred velocity against the risk of colliding soon,
+/// in the spirit of [reciprocal velocity obstacles](https://gamma.cs.unc.edu/RVO/).
+fn choose_velocity(
+    position: Vec3,
+    current_velocity: Vec3,
+    preferred_velocity: Vec3,
+    radius: f32,
+    neighbours: &[Neighbour],
+    up: Vec3,
+    config: &SteeringConfig,
+) -> Vec3 {
+    if neighbours.is_empty() {
+        return preferred_velocity;
+    }
+    let angle_step = FRAC_PI_2 / CANDIDATE_ANGLE_COUNT as f32;
+    (-CANDIDATE_ANGLE_COUNT..=CANDIDATE_ANGLE_COUNT)
+        .flat_map(|step| {
+            let rotation = Quat::from_axis_angle(up, step as f32 * angle_step);
+            CANDIDATE_SPEED_FACTORS
+                .iter()
+                .map(move |factor| rotation * preferred_velocity * *factor)
+        })
+        .map(|candidate| {
+            let time_to_collision = neighbours
+                .iter()
+                .filter_map(|neighbour| {
+                    // A reciprocating neighbour is expected to do half of the avoiding
+                    let relative_velocity = if neighbour.reciprocal {
+                        2.0 * candidate - current_velocity - neighbour.velocity
+                    } else {
+                        candidate - neighbour.velocity
+                    };
+                    get_time_to_collision(
+                        (neighbour.position - position).split(up).horizontal,
+                        relative_velocity,
+                        radius + neighbour.radius,
+                    )
+                })
+                .fold(f32::INFINITY, f32::min);
+            let collision_penalty = if time_to_collision < config.avoidance_time_horizon {
+                config.avoidance_weight / time_to_collision.max(1e-3)
+            } else {
+                0.0
+            };
+            let deviation_penalty = (candidate - preferred_velocity).length();
+            (candidate, collision_penalty + deviation_penalty)
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _penalty)| candidate)
+        .unwrap_or(preferred_velocity)
+}
+
+/// Time until two discs, one at the origin and one at `relative_position`, touch when the first moves with `relative_velocity`.
+/// Returns [`None`] if they never touch.
+fn get_time_to_collision(
+    relative_position: Vec3,
+    relative_velocity: Vec3,
+    combined_radius: f32,
+) -> Option<f32> {
+    let distance_squared = relative_position.length_squared() - combined_radius.squared();
+    if distance_squared < 0.0 {
+        // Already overlapping
+        return Some(0.0);
+    }
+    let a = relative_velocity.length_squared();
+    if a.is_approx_zero() {
+        return None;
+    }
+    let b = relative_position.dot(relative_velocity);
+    let discriminant = b.squared() - a * distance_squared;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (b - discriminant.sqrt()) / a;
+    (time >= 0.0).then_some(time)
+}
+
+/// Feels ahead and to both sides of the agent for static geometry and steers along the surface that would be hit.
+fn get_obstacle_avoidance(
+    entity: Entity,
+    position: Vec3,
+    direction: Vec3,
+    up: Vec3,
+    rapier_context: &RapierContext,
+    config: &SteeringConfig,
+) -> Vec3 {
+    let Some(direction) = direction.try_normalize() else {
+        return Vec3::ZERO;
+    };
+    let filter = QueryFilter::only_fixed()
+        .exclude_collider(entity)
+        .exclude_sensors();
+    [0.0_f32, 30.0, -30.0]
+        .into_iter()
+        .map(|angle| Quat::from_axis_angle(up, angle.to_radians()) * direction)
+        .filter_map(|feeler| {
+            rapier_context
+                .cast_ray_and_get_normal(position, feeler, config.obstacle_lookahead, true, filter)
+                .map(|(_entity, intersection)| {
+                    let urgency = 1.0 - intersection.toi / config.obstacle_lookahead;
+                    let normal = intersection.normal.split(up).horizontal;
+                    normal.try_normalize().unwrap_or_default() * urgency
+                })
+        })
+        .sum()
+}
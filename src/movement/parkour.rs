@@ -0,0 +1,363 @@
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::movement::general_movement::{
+    Climbing, GeneralMovementSystemSet, Grounded, Jumping, Swimming, Walking,
+};
+use crate::simulation::SimulationSet;
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Distance in m to a mantle target at which the mantle is considered done
+const MANTLE_ARRIVAL_DISTANCE: f32 = 0.1;
+/// How far in m a character is lifted above a ledge so that it does not start out touching it
+const MANTLE_SKIN: f32 = 0.02;
+/// Walls whose normals point up or down by more than this are floors or ceilings, not walls
+const MAX_WALL_NORMAL_VERTICAL: f32 = 0.2;
+
+/// Handles the optional traversal moves of a level: [`Mantling`] onto ledges and [`WallRunning`] along vertical surfaces.
+/// Both are off by default and need to be allowed by the level, see [`ParkourRules`].
+pub(crate) fn parkour_plugin(app: &mut App) {
+    app.register_type::<ParkourRules>()
+        .register_type::<Mantling>()
+        .register_type::<WallRunning>()
+        .init_resource::<ParkourRules>()
+        .add_system(read_parkour_rules.in_set(OnUpdate(GameState::Playing)))
+        .add_systems(
+            (apply_mantling, apply_wall_running)
+                .chain()
+                .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// Which traversal moves the current level allows. Reset whenever a level is loaded and enabled by tagging any object in the level
+/// with `[allow_mantling]` or `[allow_wall_running]`, so traditional levels are not affected.
+#[derive(Debug, Clone, PartialEq, Eq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct ParkourRules {
+    pub(crate) mantling: bool,
+    pub(crate) wall_running: bool,
+}
+
+/// Lets a character climb onto ledges in front of it by jumping towards them.
+/// The ledge is found with shape casts of the character's own collider, and the character is then moved onto it via [`Climbing`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Mantling {
+    /// Lowest ledge in m above the feet that is mantled instead of simply jumped onto
+    pub(crate) min_height: f32,
+    /// Highest ledge in m above the feet that can be grabbed, about chest height
+    pub(crate) max_height: f32,
+    /// Distance in m in front of the character in which ledges are searched
+    pub(crate) reach: f32,
+    /// Speed in m/s with which the character climbs onto the ledge
+    pub(crate) speed: f32,
+    /// Seconds after which a mantle that got stuck is aborted
+    pub(crate) max_seconds: f32,
+    /// Where the center of the character will be once it stands on the ledge
+    pub(crate) target: Option<Vec3>,
+    pub(crate) elapsed_seconds: f32,
+}
+
+impl Default for Mantling {
+    fn default() -> Self {
+        Self {
+            min_height: 0.4,
+            max_height: 1.3,
+            reach: 0.5,
+            speed: 3.0,
+            max_seconds: 1.0,
+            target: None,
+            elapsed_seconds: 0.0,
+        }
+    }
+}
+
+/// Lets a character run along walls while sprinting in the air. Gravity is reduced while running,
+/// and jumping pushes the character off the wall.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct WallRunning {
+    /// Horizontal speed in m/s needed to start and keep running
+    pub(crate) min_speed: f32,
+    /// Seconds that can be spent on walls before touching the ground again
+    pub(crate) max_seconds: f32,
+    /// Gravity scale while running
+    pub(crate) gravity_scale: f32,
+    /// Distance in m between the character's collider and a wall at which the wall can be run on
+    pub(crate) reach: f32,
+    /// Speed in m/s with which the character jumps off the wall, both up and away from it
+    pub(crate) jump_off_speed: f32,
+    /// Normal of the wall that is currently being run on
+    pub(crate) wall_normal: Option<Vec3>,
+    pub(crate) elapsed_seconds: f32,
+}
+
+impl Default for WallRunning {
+    fn default() -> Self {
+        Self {
+            min_speed: 4.0,
+            max_seconds: 1.5,
+            gravity_scale: 0.2,
+            reach: 0.3,
+            jump_off_speed: 4.0,
+            wall_normal: None,
+            elapsed_seconds: 0.0,
+        }
+    }
+}
+
+fn read_parkour_rules(
+    current_level: Option<Res<CurrentLevel>>,
+    added_name: Query<&Name, Added<Name>>,
+    mut rules: ResMut<ParkourRules>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_parkour_rules").entered();
+    if current_level.map_or(false, |level| level.is_changed()) {
+        *rules = default();
+    }
+    for name in &added_name {
+        let name = name.to_lowercase();
+        if name.contains("[allow_mantling]") {
+            rules.mantling = true;
+        }
+        if name.contains("[allow_wall_running]") {
+            rules.wall_running = true;
+        }
+    }
+}
+
+fn apply_mantling(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    rules: Res<ParkourRules>,
+    mut characters: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &mut Mantling,
+            &mut Jumping,
+            Option<&mut Climbing>,
+        ),
+        Without<Swimming>,
+    >,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_mantling").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (entity, transform, collider, mut mantling, mut jumping, climbing) in &mut characters {
+        let Some(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+        let up = transform.up();
+        if let Some(target) = mantling.target {
+            mantling.elapsed_seconds += dt;
+            let to_target = target - transform.translation;
+            let is_done = to_target.length_squared() < MANTLE_ARRIVAL_DISTANCE.squared()
+                || mantling.elapsed_seconds > mantling.max_seconds;
+            match climbing {
+                Some(mut climbing) if !is_done => {
+                    // Rise above the ledge first so that we don't get caught on its edge
+                    let height_to_target = to_target.dot(up);
+                    climbing.direction = Some(if height_to_target > MANTLE_ARRIVAL_DISTANCE {
+                        up
+                    } else {
+                        to_target.normalize_or_zero()
+                    });
+                }
+                Some(_) => {
+                    mantling.target = None;
+                    entity_commands.remove::<Climbing>();
+                }
+                // Something else, e.g. a ladder, took over
+                None => mantling.target = None,
+            }
+            continue;
+        }
+
+        if !rules.mantling || !jumping.requested || climbing.is_some() {
+            continue;
+        }
+        let Some(target) =
+            find_ledge(entity, transform, collider, &mantling, &rapier_context)
+        else {
+            continue;
+        };
+        jumping.requested = false;
+        mantling.target = Some(target);
+        mantling.elapsed_seconds = 0.0;
+        entity_commands.insert(Climbing {
+            speed: mantling.speed,
+            direction: Some(up),
+        });
+    }
+}
+
+/// Returns the position of the character's center when standing on the ledge in front of it, if there is one within reach.
+fn find_ledge(
+    entity: Entity,
+    transform: &Transform,
+    collider: &Collider,
+    mantling: &Mantling,
+    rapier_context: &RapierContext,
+) -> Option<Vec3> {
+    let up = transform.up();
+    let forward = transform.forward().split(up).horizontal.try_normalize()?;
+    let filter = QueryFilter::new()
+        .exclude_collider(entity)
+        .exclude_sensors();
+    let rotation = transform.rotation;
+
+    // Is there a wall in front of us?
+    let (_, wall_hit) = rapier_context.cast_shape(
+        transform.translation,
+        rotation,
+        forward,
+        collider,
+        mantling.reach,
+        filter,
+    )?;
+
+    // Is there room to stand above it?
+    let radius = collider.raw.compute_local_aabb().maxs.x;
+    let above_ledge =
+        transform.translation + forward * (wall_hit.toi + radius) + up * mantling.max_height;
+    if rapier_context
+        .intersection_with_shape(above_ledge, rotation, collider, filter)
+        .is_some()
+    {
+        return None;
+    }
+
+    // Is the top of the wall high enough to count as a ledge?
+    let (_, ledge_hit) = rapier_context.cast_shape(
+        above_ledge,
+        rotation,
+        -up,
+        collider,
+        mantling.max_height - mantling.min_height,
+        filter,
+    )?;
+    Some(above_ledge - up * (ledge_hit.toi - MANTLE_SKIN))
+}
+
+fn apply_wall_running(
+    fixed_time: Res<FixedTime>,
+    rules: Res<ParkourRules>,
+    mut characters: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &Grounded,
+            &Walking,
+            &mut Jumping,
+            &mut Velocity,
+            &mut GravityScale,
+            &mut WallRunning,
+        ),
+        (Without<Climbing>, Without<Swimming>),
+    >,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_wall_running").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (
+        entity,
+        transform,
+        collider,
+        grounded,
+        walking,
+        mut jumping,
+        mut velocity,
+        mut gravity_scale,
+        mut wall_running,
+    ) in &mut characters
+    {
+        if grounded.is_grounded() {
+            wall_running.elapsed_seconds = 0.0;
+        }
+        let up = transform.up();
+        let horizontal_velocity = velocity.linvel.split(up).horizontal;
+        let can_run = rules.wall_running
+            && !grounded.is_grounded()
+            && walking.sprinting
+            && horizontal_velocity.length_squared() >= wall_running.min_speed.squared()
+            && wall_running.elapsed_seconds < wall_running.max_seconds;
+        let wall_normal = can_run
+            .then(|| {
+                find_wall(
+                    entity,
+                    transform,
+                    collider,
+                    horizontal_velocity,
+                    &wall_running,
+                    &rapier_context,
+                )
+            })
+            .flatten();
+
+        let Some(wall_normal) = wall_normal else {
+            if wall_running.wall_normal.take().is_some() {
+                gravity_scale.0 = 1.0;
+            }
+            continue;
+        };
+        let vertical_speed = velocity.linvel.dot(up);
+        if wall_running.wall_normal.is_none() && vertical_speed < 0.0 {
+            // Catch ourselves when starting to run instead of sliding down the wall
+            velocity.linvel -= up * vertical_speed;
+        }
+        if jumping.requested {
+            jumping.requested = false;
+            velocity.linvel += (wall_normal + up) * wall_running.jump_off_speed;
+            wall_running.wall_normal = None;
+            gravity_scale.0 = 1.0;
+            continue;
+        }
+        // Run along the wall, neither into it nor away from it
+        velocity.linvel -= wall_normal * velocity.linvel.dot(wall_normal);
+        gravity_scale.0 = wall_running.gravity_scale;
+        wall_running.wall_normal = Some(wall_normal);
+        wall_running.elapsed_seconds += dt;
+    }
+}
+
+/// Returns the normal of a wall to the left or right of the character, if there is one within reach.
+fn find_wall(
+    entity: Entity,
+    transform: &Transform,
+    collider: &Collider,
+    horizontal_velocity: Vec3,
+    wall_running: &WallRunning,
+    rapier_context: &RapierContext,
+) -> Option<Vec3> {
+    let up = transform.up();
+    let side = horizontal_velocity.cross(up).try_normalize()?;
+    let radius = collider.raw.compute_local_aabb().maxs.x;
+    let filter = QueryFilter::new()
+        .exclude_collider(entity)
+        .exclude_sensors();
+    // Prefer the wall we are already running on
+    let sides = match wall_running.wall_normal {
+        Some(normal) if normal.dot(side) > 0.0 => [-side, side],
+        _ => [side, -side],
+    };
+    sides.into_iter().find_map(|direction| {
+        let (_, hit) = rapier_context.cast_ray_and_get_normal(
+            transform.translation,
+            direction,
+            radius + wall_running.reach,
+            true,
+            filter,
+        )?;
+        (hit.normal.dot(up).abs() < MAX_WALL_NORMAL_VERTICAL).then_some(hit.normal)
+    })
+}
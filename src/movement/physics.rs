@@ -1,22 +1,62 @@
+use crate::movement::navigation::navmesh_cache::NavMeshInput;
+use crate::movement::navigation::obstacle::NavMeshObstacle;
+use crate::simulation::{is_simulation_running, SimulationSet, TICKS_PER_SECOND};
 use crate::util::trait_extension::MeshExt;
 use crate::GameState;
 use anyhow::{Context, Result};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
 use bevy_mod_sysfail::macros::*;
 use bevy_rapier3d::prelude::*;
 use oxidized_navigation::NavMeshAffector;
 
 /// Sets up the [`RapierPhysicsPlugin`] and [`RapierConfiguration`].
+/// The physics step is part of the fixed rate simulation and runs once per tick, right after [`SimulationSet::Gameplay`].
 pub(crate) fn physics_plugin(app: &mut App) {
-    app.add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+    app.add_plugin(RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false))
         .insert_resource(RapierConfiguration {
-            timestep_mode: TimestepMode::Variable {
-                max_dt: 1.0 / 20.0,
-                time_scale: 1.0,
-                substeps: 4,
+            timestep_mode: TimestepMode::Fixed {
+                dt: (1.0 / TICKS_PER_SECOND) as f32,
+                substeps: 2,
             },
             ..default()
         })
+        .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+            schedule
+                .configure_sets(
+                    (
+                        PhysicsSet::SyncBackend,
+                        PhysicsSet::SyncBackendFlush,
+                        PhysicsSet::StepSimulation,
+                        PhysicsSet::Writeback,
+                    )
+                        .chain()
+                        .after(SimulationSet::Gameplay)
+                        .before(SimulationSet::Store),
+                )
+                .configure_set(PhysicsSet::StepSimulation.run_if(is_simulation_running));
+        })
+        .add_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                .in_base_set(PhysicsSet::SyncBackend)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                .in_base_set(PhysicsSet::SyncBackendFlush)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                .in_base_set(PhysicsSet::StepSimulation)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                .in_base_set(PhysicsSet::Writeback)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
         .add_system(read_colliders.in_set(OnUpdate(GameState::Playing)));
 }
 
@@ -31,7 +71,8 @@ pub(crate) fn read_colliders(
     #[cfg(feature = "tracing")]
     let _span = info_span!("read_colliders").entered();
     for (entity, name) in &added_name {
-        if name.to_lowercase().contains("[collider]") {
+        let name = name.to_lowercase();
+        if name.contains("[collider]") {
             for (collider_entity, collider_mesh) in
                 Mesh::search_in_children(entity, &children, &meshes, &mesh_handles)
             {
@@ -40,10 +81,77 @@ pub(crate) fn read_colliders(
                         .context("Failed to create collider from mesh")?;
 
                 if let Some(mut entity_commands) = commands.get_entity(collider_entity) {
-                    entity_commands.insert((rapier_collider, NavMeshAffector));
+                    if name.contains("[dynamic]") {
+                        entity_commands.insert((rapier_collider, NavMeshAffector, NavMeshObstacle));
+                    } else {
+                        entity_commands.insert((rapier_collider, NavMeshInput::Collider));
+                    }
                 }
             }
         }
     }
     Ok(())
 }
+
+/// Turns the meshes of name-tagged objects into invisible convex sensor colliders that characters can be inside of,
+/// like the `[killzone]`, `[ladder]` and `[water]` volumes. See [`SensorVolumeReader::read`].
+#[derive(SystemParam)]
+pub(crate) struct SensorVolumeReader<'w, 's> {
+    commands: Commands<'w, 's>,
+    added_name: Query<'w, 's, (Entity, &'static Name), Added<Name>>,
+    children: Query<'w, 's, &'static Children>,
+    meshes: Res<'w, Assets<Mesh>>,
+    mesh_handles: Query<'w, 's, &'static Handle<Mesh>>,
+    global_transforms: Query<'w, 's, &'static GlobalTransform>,
+}
+
+impl SensorVolumeReader<'_, '_> {
+    /// Gives every mesh of a newly named object tagged with `tag` a sensor collider and the marker created from the mesh's vertices in world space.
+    /// Returns the tagged objects along with the world space vertices of all their meshes.
+    pub(crate) fn read<C: Component>(
+        &mut self,
+        tag: &str,
+        create_marker: impl Fn(&[Vec3]) -> C,
+    ) -> Result<Vec<(Entity, Vec<Vec3>)>> {
+        let mut volumes = Vec::new();
+        for (parent, name) in &self.added_name {
+            let name = name.to_lowercase();
+            if !name.contains(tag) {
+                continue;
+            }
+            let mut world_points = Vec::new();
+            for (child, mesh) in
+                Mesh::search_in_children(parent, &self.children, &self.meshes, &self.mesh_handles)
+            {
+                // A trimesh would only register characters crossing its surface, not the ones inside of it
+                let Some(VertexAttributeValues::Float32x3(positions)) =
+                    mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                else {
+                    continue;
+                };
+                let points: Vec<_> = positions.iter().copied().map(Vec3::from).collect();
+                let collider = Collider::convex_hull(&points)
+                    .with_context(|| format!("Failed to create sensor collider for {name}"))?;
+                let global_transform = self
+                    .global_transforms
+                    .get(child)
+                    .with_context(|| format!("Failed to get global transform of {name}"))?;
+                let mesh_world_points: Vec<_> = points
+                    .iter()
+                    .map(|point| global_transform.transform_point(*point))
+                    .collect();
+                if let Some(mut entity_commands) = self.commands.get_entity(child) {
+                    entity_commands.insert((
+                        collider,
+                        Sensor,
+                        create_marker(&mesh_world_points),
+                        Visibility::Hidden,
+                    ));
+                }
+                world_points.extend(mesh_world_points);
+            }
+            volumes.push((parent, world_points));
+        }
+        Ok(volumes)
+    }
+}
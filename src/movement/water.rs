@@ -0,0 +1,328 @@
+use crate::combat::hazards::{kill_or_respawn, FallDamage, Respawnable};
+use crate::combat::health::{Health, Invulnerable};
+use crate::combat::shoot::{PhysicsProjectile, TracingProjectile};
+use crate::movement::general_movement::{GeneralMovementSystemSet, Grounded, Swimming};
+use crate::movement::physics::SensorVolumeReader;
+use crate::particles::{ParticleEffects, TimedParticle};
+use crate::player_control::camera::IngameCamera;
+use crate::simulation::SimulationSet;
+use crate::spatial_audio::{AudioEmitterHandle, Muffled};
+use crate::GameState;
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_hanabi::{ParticleEffect, ParticleEffectBundle};
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Characters start swimming once this much of them is below the surface
+const ENTER_SUBMERSION: f32 = 0.6;
+/// Characters stop swimming once less than this much of them is below the surface, e.g. when walking up a beach
+const EXIT_SUBMERSION: f32 = 0.4;
+/// Height of the eyes relative to the top of a character's collider, used to determine whether its head is underwater
+const EYE_HEIGHT_FACTOR: f32 = 0.8;
+/// Factor applied to the speed of projectiles when they hit the water
+const PROJECTILE_SPEED_FACTOR: f32 = 0.2;
+/// Factor applied to the volume of sounds from outside the water while the camera is underwater
+const UNDERWATER_VOLUME: f32 = 0.35;
+
+/// Handles bodies of water: characters swim in them, run out of [`Breath`] below the surface and projectiles slow down when entering them.
+/// While the camera is underwater, the view is fogged and sounds from outside the water are muffled.
+pub(crate) fn water_plugin(app: &mut App) {
+    app.register_type::<Water>()
+        .register_type::<Breath>()
+        .register_type::<Submerged>()
+        .add_system(
+            read_water
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_system(
+            detect_water
+                .before(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            (update_breath, slow_projectiles_in_water)
+                .after(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(apply_underwater_effects.in_set(OnUpdate(GameState::Playing)));
+}
+
+/// A body of water, authored in the level by tagging an object with `[water]`. Its meshes become invisible convex sensor colliders,
+/// and the top of each mesh is considered the water surface.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Water {
+    pub(crate) surface_height: f32,
+}
+
+/// Runs out while the character's head is underwater. Afterwards, characters with [`Health`] drown slowly
+/// and [`Respawnable`] characters are put back onto solid ground.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Breath {
+    pub(crate) max_seconds: f32,
+    pub(crate) remaining_seconds: f32,
+    /// How many times faster breath is regained than lost
+    pub(crate) refill_factor: f32,
+    pub(crate) drowning_damage_per_second: f32,
+}
+
+impl Default for Breath {
+    fn default() -> Self {
+        Self {
+            max_seconds: 15.0,
+            remaining_seconds: 15.0,
+            refill_factor: 3.0,
+            drowning_damage_per_second: 10.0,
+        }
+    }
+}
+
+impl Breath {
+    pub(crate) fn get_fraction(&self) -> f32 {
+        if self.max_seconds <= 0.0 {
+            return 0.0;
+        }
+        (self.remaining_seconds / self.max_seconds).clamp(0.0, 1.0)
+    }
+}
+
+/// Marks projectiles that already hit the water, so that they are only slowed down once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct Submerged;
+
+#[sysfail(log(level = "error"))]
+fn read_water(mut sensor_volumes: SensorVolumeReader) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_water").entered();
+    sensor_volumes.read("[water]", |points| Water {
+        surface_height: points
+            .iter()
+            .map(|point| point.y)
+            .fold(f32::NEG_INFINITY, f32::max),
+    })?;
+    Ok(())
+}
+
+fn detect_water(
+    mut commands: Commands,
+    mut characters: Query<
+        (Entity, &Transform, &Collider, &mut Damping, Option<&mut Swimming>),
+        With<Grounded>,
+    >,
+    waters: Query<&Water>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("detect_water").entered();
+    for (entity, transform, collider, mut damping, swimming) in &mut characters {
+        let surface_height = rapier_context
+            .intersections_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == entity { b } else { a })
+            .find_map(|other| waters.get(other).ok())
+            .map(|water| water.surface_height);
+        let half_height = collider.raw.compute_local_aabb().maxs.y;
+        let submersion = surface_height.map_or(0.0, |surface_height| {
+            let feet = transform.translation.y - half_height;
+            ((surface_height - feet) / (2.0 * half_height)).clamp(0.0, 1.0)
+        });
+
+        let Some(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+        match (swimming, surface_height) {
+            (Some(mut swimming), Some(surface_height)) if submersion >= EXIT_SUBMERSION => {
+                swimming.surface_height = surface_height;
+                swimming.submersion = submersion;
+            }
+            (Some(swimming), _) => {
+                damping.linear_damping = swimming.dry_linear_damping;
+                entity_commands.remove::<Swimming>();
+            }
+            (None, Some(surface_height)) if submersion > ENTER_SUBMERSION => {
+                let swimming = Swimming {
+                    dry_linear_damping: damping.linear_damping,
+                    surface_height,
+                    submersion,
+                    ..default()
+                };
+                damping.linear_damping = swimming.linear_damping;
+                entity_commands.insert(swimming);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_breath(
+    fixed_time: Res<FixedTime>,
+    mut characters: Query<(
+        &mut Breath,
+        &mut Transform,
+        &mut Velocity,
+        &Collider,
+        Option<&Swimming>,
+        Option<&Respawnable>,
+        Option<&mut Health>,
+        Option<&mut FallDamage>,
+        Option<&Invulnerable>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_breath").entered();
+    let dt = fixed_time.period.as_secs_f32();
+    for (
+        mut breath,
+        mut transform,
+        mut velocity,
+        collider,
+        swimming,
+        respawnable,
+        health,
+        fall_damage,
+        invulnerable,
+    ) in &mut characters
+    {
+        let eye_height =
+            transform.translation.y + collider.raw.compute_local_aabb().maxs.y * EYE_HEIGHT_FACTOR;
+        let is_head_underwater =
+            swimming.map_or(false, |swimming| eye_height < swimming.surface_height);
+        if !is_head_underwater {
+            breath.remaining_seconds =
+                (breath.remaining_seconds + dt * breath.refill_factor).min(breath.max_seconds);
+            continue;
+        }
+        breath.remaining_seconds = (breath.remaining_seconds - dt).max(0.0);
+        if breath.remaining_seconds > 0.0 {
+            continue;
+        }
+        if let Some(mut health) = health {
+            health.take_damage(breath.drowning_damage_per_second * dt, invulnerable);
+        } else if respawnable.is_some() {
+            kill_or_respawn(
+                &mut transform,
+                &mut velocity,
+                respawnable,
+                None,
+                fall_damage,
+                invulnerable,
+            );
+            breath.remaining_seconds = breath.max_seconds;
+        }
+    }
+}
+
+fn slow_projectiles_in_water(
+    mut commands: Commands,
+    mut projectiles: Query<
+        (Entity, &Transform, Option<&mut TracingProjectile>, Option<&mut Velocity>),
+        (Or<(With<TracingProjectile>, With<PhysicsProjectile>)>, Without<Submerged>),
+    >,
+    waters: Query<&Water>,
+    rapier_context: Res<RapierContext>,
+    particle_effects: Res<ParticleEffects>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("slow_projectiles_in_water").entered();
+    for (entity, transform, tracing_projectile, velocity) in &mut projectiles {
+        let Some(water) = find_water_at(transform.translation, &waters, &rapier_context) else {
+            continue;
+        };
+        if let Some(mut tracing_projectile) = tracing_projectile {
+            tracing_projectile.velocity *= PROJECTILE_SPEED_FACTOR;
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.linvel *= PROJECTILE_SPEED_FACTOR;
+        }
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(Submerged);
+        }
+        if let Some(splash) = particle_effects.splash.clone() {
+            let mut splash_translation = transform.translation;
+            splash_translation.y = water.surface_height;
+            commands.spawn((
+                Name::new("Splash particle"),
+                ParticleEffectBundle {
+                    effect: ParticleEffect::new(splash),
+                    transform: Transform::from_translation(splash_translation),
+                    ..default()
+                },
+                TimedParticle {
+                    destroy_on_completion: true,
+                    length: 1.0,
+                    time_played: 0.0,
+                },
+            ));
+        }
+    }
+}
+
+fn apply_underwater_effects(
+    mut commands: Commands,
+    cameras: Query<(Entity, &GlobalTransform, Option<&FogSettings>), With<IngameCamera>>,
+    emitters: Query<(Entity, &GlobalTransform, Option<&Muffled>), With<AudioEmitterHandle>>,
+    waters: Query<&Water>,
+    rapier_context: Res<RapierContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_underwater_effects").entered();
+    let Some((entity, transform, fog)) = cameras.iter().next() else {
+        return;
+    };
+    let is_underwater =
+        find_water_at(transform.translation(), &waters, &rapier_context).is_some();
+    for (emitter_entity, emitter_transform, muffled) in &emitters {
+        // Sounds that are underwater themselves stay audible
+        let should_muffle = is_underwater
+            && find_water_at(emitter_transform.translation(), &waters, &rapier_context).is_none();
+        if should_muffle == muffled.is_some() {
+            continue;
+        }
+        let Some(mut emitter_commands) = commands.get_entity(emitter_entity) else {
+            continue;
+        };
+        if should_muffle {
+            emitter_commands.insert(Muffled {
+                volume: UNDERWATER_VOLUME,
+            });
+        } else {
+            emitter_commands.remove::<Muffled>();
+        }
+    }
+    let Some(mut entity_commands) = commands.get_entity(entity) else {
+        return;
+    };
+    if is_underwater && fog.is_none() {
+        entity_commands.insert(FogSettings {
+            color: Color::rgba(0.05, 0.2, 0.3, 1.0),
+            falloff: FogFalloff::Linear {
+                start: 0.0,
+                end: 12.0,
+            },
+            ..default()
+        });
+    } else if !is_underwater && fog.is_some() {
+        entity_commands.remove::<FogSettings>();
+    }
+}
+
+fn find_water_at<'a>(
+    point: Vec3,
+    waters: &'a Query<&Water>,
+    rapier_context: &RapierContext,
+) -> Option<&'a Water> {
+    let mut water = None;
+    rapier_context.intersections_with_point(point, QueryFilter::new(), |entity| {
+        water = waters.get(entity).ok();
+        water.is_none()
+    });
+    water
+}
@@ -1,6 +1,6 @@
 use crate::file_system_interaction::config::GameConfig;
 use crate::level_instantiation::spawning::objects::player;
-use crate::movement::general_movement::Grounded;
+use crate::movement::general_movement::{Dashing, Grounded};
 use crate::particles::init::init_effects;
 use crate::util::trait_extension::{F32Ext, Vec3Ext};
 use crate::GameState;
@@ -13,9 +13,11 @@ mod init;
 /// Handles particle effects instantiation and playing.
 pub(crate) fn particle_plugin(app: &mut App) {
     app.register_type::<SprintingParticle>()
+        .register_type::<DashingParticle>()
         .add_plugin(HanabiPlugin)
         .add_system(init_effects.in_schedule(OnExit(GameState::Loading)))
         .add_system(play_sprinting_effect.in_set(OnUpdate(GameState::Playing)))
+        .add_system(play_dashing_effect.in_set(OnUpdate(GameState::Playing)))
         .add_system(handle_timed_particles.in_set(OnUpdate(GameState::Playing)))
         .insert_resource(ParticleEffects::default());
 }
@@ -24,6 +26,7 @@ pub(crate) fn particle_plugin(app: &mut App) {
 #[reflect(Resource)]
 pub(crate) struct ParticleEffects {
     pub(crate) firework: Option<Handle<EffectAsset>>,
+    pub(crate) splash: Option<Handle<EffectAsset>>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Default)]
@@ -36,14 +39,14 @@ fn play_sprinting_effect(
     config: Res<GameConfig>,
 ) {
     for (player_transform, grounded, velocity) in with_player.iter() {
-        let horizontal_speed_squared = velocity
-            .linvel
+        let horizontal_speed_squared = grounded
+            .get_relative_velocity(velocity.linvel)
             .split(player_transform.up())
             .horizontal
             .length_squared();
         for (mut particle_transform, mut effect) in with_particle.iter_mut() {
             let threshold = config.player.sprint_effect_speed_threshold;
-            if grounded.0 && horizontal_speed_squared > threshold.squared() {
+            if grounded.is_grounded() && horizontal_speed_squared > threshold.squared() {
                 let translation = player_transform.translation
                     - player_transform.up() * (player::HEIGHT / 2. + player::RADIUS);
                 *particle_transform = player_transform.with_translation(translation);
@@ -55,6 +58,28 @@ fn play_sprinting_effect(
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+struct DashingParticle;
+
+fn play_dashing_effect(
+    with_player: Query<(&Transform, &Dashing), Without<DashingParticle>>,
+    mut with_particle: Query<(&mut Transform, &mut ParticleEffect), With<DashingParticle>>,
+) {
+    for (player_transform, dashing) in with_player.iter() {
+        for (mut particle_transform, mut effect) in with_particle.iter_mut() {
+            if let Some(spawner) = effect.maybe_spawner() {
+                if dashing.is_dashing() {
+                    *particle_transform = *player_transform;
+                    spawner.set_active(true);
+                } else {
+                    spawner.set_active(false);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
 #[reflect(Component)]
 pub(crate) struct TimedParticle {
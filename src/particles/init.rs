@@ -1,5 +1,5 @@
 use crate::level_instantiation::spawning::objects::player;
-use crate::particles::SprintingParticle;
+use crate::particles::{DashingParticle, SprintingParticle};
 use bevy::pbr::NotShadowReceiver;
 use bevy::prelude::*;
 use bevy_hanabi::prelude::*;
@@ -22,8 +22,22 @@ pub(crate) fn init_effects(
         NotShadowReceiver,
     ));
 
+    let dashing = create_dashing_effect(&mut effects);
+    commands.spawn((
+        Name::new("Dashing particle"),
+        DashingParticle,
+        ParticleEffectBundle {
+            effect: dashing,
+            ..default()
+        },
+        NotShadowReceiver,
+    ));
+
     let firework_handle = create_firework_effect(&mut effects);
     particle_effects.firework = Some(firework_handle);
+
+    let splash_handle = create_splash_effect(&mut effects);
+    particle_effects.splash = Some(splash_handle);
 }
 
 fn create_sprinting_effect(effects: &mut Assets<EffectAsset>) -> ParticleEffect {
@@ -73,6 +87,48 @@ fn create_sprinting_effect(effects: &mut Assets<EffectAsset>) -> ParticleEffect
     )
 }
 
+fn create_dashing_effect(effects: &mut Assets<EffectAsset>) -> ParticleEffect {
+    let mut color_gradient = Gradient::new();
+    color_gradient.add_key(0.0, Vec4::new(1.5, 1.5, 2.0, 0.8));
+    color_gradient.add_key(0.5, Vec4::new(1.0, 1.0, 1.5, 0.3));
+    color_gradient.add_key(1.0, Vec4::new(1.0, 1.0, 1.5, 0.0));
+
+    let mut size_gradient = Gradient::new();
+    size_gradient.add_key(0.0, Vec2::splat(0.15));
+    size_gradient.add_key(1.0, Vec2::splat(0.02));
+
+    ParticleEffect::new(
+        effects.add(
+            EffectAsset {
+                name: "Dash".to_string(),
+                capacity: 200,
+                spawner: Spawner::rate(120.0.into()).with_active(false),
+                ..Default::default()
+            }
+            .init(InitPositionSphereModifier {
+                center: Vec3::ZERO,
+                radius: player::RADIUS,
+                dimension: ShapeDimension::Volume,
+            })
+            .init(InitVelocitySphereModifier {
+                speed: 0.3_f32.into(),
+                center: Vec3::ZERO,
+            })
+            .init(InitLifetimeModifier {
+                lifetime: 0.4.into(),
+            })
+            .update(LinearDragModifier { drag: 3. })
+            .render(BillboardModifier {})
+            .render(ColorOverLifetimeModifier {
+                gradient: color_gradient,
+            })
+            .render(SizeOverLifetimeModifier {
+                gradient: size_gradient,
+            }),
+        ),
+    )
+}
+
 fn create_firework_effect(effects: &mut Assets<EffectAsset>) -> Handle<EffectAsset> {
     let mut color_gradient1 = Gradient::new();
     color_gradient1.add_key(0.0, Vec4::new(4.0, 4.0, 4.0, 1.0));
@@ -122,3 +178,43 @@ fn create_firework_effect(effects: &mut Assets<EffectAsset>) -> Handle<EffectAss
     );
     firework
 }
+
+fn create_splash_effect(effects: &mut Assets<EffectAsset>) -> Handle<EffectAsset> {
+    let mut color_gradient = Gradient::new();
+    color_gradient.add_key(0.0, Vec4::new(0.8, 0.9, 1.0, 0.9));
+    color_gradient.add_key(1.0, Vec4::new(0.8, 0.9, 1.0, 0.0));
+
+    let mut size_gradient = Gradient::new();
+    size_gradient.add_key(0.0, Vec2::splat(0.05));
+    size_gradient.add_key(1.0, Vec2::splat(0.02));
+
+    effects.add(
+        EffectAsset {
+            name: "splash".to_string(),
+            capacity: 256,
+            spawner: Spawner::once(60.0.into(), true),
+            ..Default::default()
+        }
+        .init(InitPositionCircleModifier {
+            dimension: ShapeDimension::Volume,
+            radius: 0.1,
+            center: Vec3::ZERO,
+            axis: Vec3::Y,
+        })
+        .init(InitVelocitySphereModifier {
+            center: Vec3::new(0., -0.5, 0.),
+            speed: Value::Uniform((1.5, 3.)),
+        })
+        .init(InitLifetimeModifier {
+            lifetime: Value::Uniform((0.4, 0.7)),
+        })
+        .update(AccelModifier::constant(Vec3::new(0., -9.81, 0.)))
+        .render(BillboardModifier {})
+        .render(ColorOverLifetimeModifier {
+            gradient: color_gradient,
+        })
+        .render(SizeOverLifetimeModifier {
+            gradient: size_gradient,
+        }),
+    )
+}
@@ -1,20 +1,40 @@
 pub(crate) mod actions;
+pub(crate) mod aim_assist;
 pub(crate) mod camera;
+pub(crate) mod controls;
+pub(crate) mod hud;
+pub(crate) mod lock_on;
 pub(crate) mod player_embodiment;
+pub(crate) mod top_down_aim;
 
 pub(crate) use crate::player_control::actions::actions_plugin;
+pub(crate) use crate::player_control::aim_assist::aim_assist_plugin;
 pub(crate) use crate::player_control::camera::camera_plugin;
+pub(crate) use crate::player_control::controls::controls_plugin;
+pub(crate) use crate::player_control::hud::hud_plugin;
+pub(crate) use crate::player_control::lock_on::lock_on_plugin;
 pub(crate) use crate::player_control::player_embodiment::player_embodiment_plugin;
+pub(crate) use crate::player_control::top_down_aim::top_down_aim_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
 /// Handles systems exclusive to the player's control. Is split into the following sub-plugins:
 /// - [`actions_plugin`]: Handles player input such as mouse and keyboard and neatly packs it into an [`actions::Actions`] resource.
+/// - [`aim_assist_plugin`]: Helps aiming with a gamepad.
 /// - [`camera_plugin`]: Handles camera movement.
+/// - [`controls_plugin`]: Lets the player rebind their controls and persists them.
+/// - [`hud_plugin`]: Draws the heads-up display, e.g. ability cooldowns.
+/// - [`lock_on_plugin`]: Locks the camera onto an enemy during combat.
 /// - [`player_embodiment_plugin`]: Tells the components from [`super::movement_plugin`] about the desired player [`actions::Actions`].
 /// Also handles other systems that change how the player is physically represented in the world.
+/// - [`top_down_aim_plugin`]: Lets the player aim at a point on the ground while the camera looks down from a fixed angle.
 pub(crate) fn player_control_plugin(app: &mut App) {
     app.fn_plugin(actions_plugin)
+        .fn_plugin(aim_assist_plugin)
         .fn_plugin(camera_plugin)
-        .fn_plugin(player_embodiment_plugin);
+        .fn_plugin(controls_plugin)
+        .fn_plugin(hud_plugin)
+        .fn_plugin(lock_on_plugin)
+        .fn_plugin(player_embodiment_plugin)
+        .fn_plugin(top_down_aim_plugin);
 }
@@ -1,24 +1,104 @@
-use crate::util::criteria::is_frozen;
+use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
-use leafwing_input_manager::axislike::DualAxisData;
+use leafwing_input_manager::axislike::{AxisType, DualAxisData};
 use leafwing_input_manager::plugin::InputManagerSystem;
 use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::InputKind;
 use serde::{Deserialize, Serialize};
 
-#[derive(Resource, Default, Reflect, Serialize, Deserialize)]
+/// A situation in which only some actions make sense, e.g. walking around or reading a dialog.
+/// Actions that the current context does not enable are released every frame, see [`InputContextStack`].
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) enum InputContext {
+    /// Walking around, fighting and interacting with the world
+    #[default]
+    Gameplay,
+    /// Reading and answering a dialog. The camera can still be turned with a stick.
+    Dialog,
+    /// A menu or other UI that needs the cursor, e.g. the pause menu or photo mode
+    Menu,
+    /// Placing things in the level. The player can move around, but not fight or interact.
+    Build,
+    /// Watching without taking part, e.g. during a cinematic
+    Spectator,
+}
+
+impl InputContext {
+    pub(crate) fn enables_player_action(self, action: PlayerAction) -> bool {
+        let is_dialog_action = matches!(
+            action,
+            PlayerAction::SpeedUpDialog
+                | PlayerAction::NumberedChoice1
+                | PlayerAction::NumberedChoice2
+                | PlayerAction::NumberedChoice3
+                | PlayerAction::NumberedChoice4
+                | PlayerAction::NumberedChoice5
+                | PlayerAction::NumberedChoice6
+                | PlayerAction::NumberedChoice7
+                | PlayerAction::NumberedChoice8
+                | PlayerAction::NumberedChoice9
+                | PlayerAction::NumberedChoice0
+        );
+        match self {
+            InputContext::Gameplay => !is_dialog_action,
+            InputContext::Dialog => is_dialog_action,
+            InputContext::Build => matches!(
+                action,
+                PlayerAction::Move
+                    | PlayerAction::Sprint
+                    | PlayerAction::Jump
+                    | PlayerAction::Crouch
+            ),
+            InputContext::Menu | InputContext::Spectator => false,
+        }
+    }
+
+    pub(crate) fn enables_camera_action(self, _action: CameraAction) -> bool {
+        matches!(
+            self,
+            InputContext::Gameplay | InputContext::Dialog | InputContext::Build
+        )
+    }
+
+    /// Whether the cursor is released so that the UI can be clicked.
+    /// Moving the mouse towards a button then does not turn the camera.
+    pub(crate) fn frees_cursor(self) -> bool {
+        matches!(self, InputContext::Dialog | InputContext::Menu)
+    }
+}
+
+/// The [`InputContext`]s that are currently entered. Only the topmost one decides which actions are enabled,
+/// so e.g. pausing during a dialog disables the dialog choices until the pause menu is closed again.
+/// An empty stack means [`InputContext::Gameplay`].
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
 #[reflect(Resource, Serialize, Deserialize)]
-pub(crate) struct ActionsFrozen {
-    freeze_count: usize,
+pub(crate) struct InputContextStack {
+    contexts: Vec<InputContext>,
 }
-impl ActionsFrozen {
-    pub(crate) fn freeze(&mut self) {
-        self.freeze_count += 1;
+
+impl InputContextStack {
+    pub(crate) fn push(&mut self, context: InputContext) {
+        self.contexts.push(context);
     }
-    pub(crate) fn unfreeze(&mut self) {
-        self.freeze_count -= 1;
+
+    /// Leaves the topmost occurrence of the context, even if another context was entered after it.
+    pub(crate) fn pop(&mut self, context: InputContext) {
+        if let Some(index) = self
+            .contexts
+            .iter()
+            .rposition(|entered| *entered == context)
+        {
+            self.contexts.remove(index);
+        } else {
+            warn!("Tried to leave the input context {context:?}, which was not entered");
+        }
     }
-    pub(crate) fn is_frozen(&self) -> bool {
-        self.freeze_count > 0
+
+    pub(crate) fn current(&self) -> InputContext {
+        self.contexts.last().copied().unwrap_or_default()
     }
 }
 
@@ -29,26 +109,34 @@ pub(crate) fn actions_plugin(app: &mut App) {
     app.register_type::<PlayerAction>()
         .register_type::<CameraAction>()
         .register_type::<UiAction>()
-        .register_type::<ActionsFrozen>()
-        .init_resource::<ActionsFrozen>()
+        .register_type::<InputContext>()
+        .register_type::<InputContextStack>()
+        .init_resource::<InputContextStack>()
         .add_plugin(InputManagerPlugin::<PlayerAction>::default())
         .add_plugin(InputManagerPlugin::<CameraAction>::default())
         .add_plugin(InputManagerPlugin::<UiAction>::default())
         .add_system(
-            remove_actions_when_frozen
-                .run_if(is_frozen)
+            disable_actions_outside_context
                 .after(InputManagerSystem::ManualControl)
                 .in_base_set(CoreSet::PreUpdate),
         );
 }
 
-#[derive(Debug, Clone, Copy, Actionlike, Reflect, FromReflect, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Actionlike, Reflect, FromReflect, Default)]
 pub(crate) enum PlayerAction {
     #[default]
     Move,
     Sprint,
     Jump,
+    Crouch,
+    Dash,
     Shoot,
+    /// Aim down sights
+    Aim,
+    /// Toggle locking the camera onto an enemy
+    LockOn,
+    /// Where to aim in top-down mode, relative to the player
+    AimDirection,
     Interact,
     SpeedUpDialog,
     NumberedChoice1,
@@ -84,17 +172,18 @@ impl PlayerAction {
     }
 }
 
-#[derive(Debug, Clone, Actionlike, Reflect, FromReflect, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Actionlike, Reflect, FromReflect, Default)]
 pub(crate) enum CameraAction {
     #[default]
     Orbit,
     Zoom,
 }
 
-#[derive(Debug, Clone, Actionlike, Reflect, FromReflect, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Actionlike, Reflect, FromReflect, Default)]
 pub(crate) enum UiAction {
     #[default]
     TogglePause,
+    TogglePhotoMode,
 }
 
 pub(crate) fn create_player_action_input_manager_bundle() -> InputManagerBundle<PlayerAction> {
@@ -102,6 +191,8 @@ pub(crate) fn create_player_action_input_manager_bundle() -> InputManagerBundle<
         input_map: InputMap::new([
             (QwertyScanCode::Space, PlayerAction::Jump),
             (QwertyScanCode::LShift, PlayerAction::Sprint),
+            (QwertyScanCode::LControl, PlayerAction::Crouch),
+            (QwertyScanCode::Q, PlayerAction::Dash),
             (QwertyScanCode::E, PlayerAction::Interact),
             (QwertyScanCode::Space, PlayerAction::SpeedUpDialog),
             (QwertyScanCode::Key1, PlayerAction::NumberedChoice1),
@@ -117,8 +208,16 @@ pub(crate) fn create_player_action_input_manager_bundle() -> InputManagerBundle<
         ])
         .insert(MouseButton::Left, PlayerAction::Shoot)
         .insert(GamepadButtonType::RightTrigger2, PlayerAction::Shoot)
+        .insert(MouseButton::Right, PlayerAction::Aim)
+        .insert(GamepadButtonType::LeftTrigger2, PlayerAction::Aim)
+        .insert(MouseButton::Middle, PlayerAction::LockOn)
+        .insert(GamepadButtonType::RightThumb, PlayerAction::LockOn)
+        .insert(GamepadButtonType::East, PlayerAction::Crouch)
+        .insert(GamepadButtonType::LeftTrigger, PlayerAction::Dash)
+        .insert(GamepadButtonType::LeftThumb, PlayerAction::Sprint)
         .insert(VirtualDPad::wasd(), PlayerAction::Move)
         .insert(DualAxis::left_stick(), PlayerAction::Move)
+        .insert(DualAxis::right_stick(), PlayerAction::AimDirection)
         .build(),
         ..default()
     }
@@ -137,27 +236,78 @@ pub(crate) fn create_camera_action_input_manager_bundle() -> InputManagerBundle<
 
 pub(crate) fn create_ui_action_input_manager_bundle() -> InputManagerBundle<UiAction> {
     InputManagerBundle {
-        input_map: InputMap::new([(QwertyScanCode::Escape, UiAction::TogglePause)]),
+        input_map: InputMap::new([
+            (QwertyScanCode::Escape, UiAction::TogglePause),
+            (QwertyScanCode::P, UiAction::TogglePhotoMode),
+        ]),
         ..default()
     }
 }
 
-pub(crate) fn remove_actions_when_frozen(
+pub(crate) fn disable_actions_outside_context(
+    input_contexts: Res<InputContextStack>,
+    mut mouse_motion: EventReader<MouseMotion>,
     mut player_actions_query: Query<&mut ActionState<PlayerAction>>,
-    mut camera_actions_query: Query<&mut ActionState<CameraAction>>,
+    mut camera_actions_query: Query<(
+        &mut ActionState<CameraAction>,
+        Option<&InputMap<CameraAction>>,
+    )>,
 ) {
+    let context = input_contexts.current();
+    let mouse_delta: Vec2 = mouse_motion.iter().map(|motion| motion.delta).sum();
     for mut player_actions in player_actions_query.iter_mut() {
-        player_actions.action_data_mut(PlayerAction::Move).axis_pair = Some(default());
-        player_actions.release(PlayerAction::Jump);
-        player_actions.release(PlayerAction::Interact);
-        player_actions.release(PlayerAction::Sprint);
-        player_actions.release(PlayerAction::Shoot);
+        for action in PlayerAction::variants() {
+            if !context.enables_player_action(action) {
+                disable_action(&mut player_actions, action);
+            }
+        }
     }
-    for mut camera_actions in camera_actions_query.iter_mut() {
-        camera_actions
-            .action_data_mut(CameraAction::Orbit)
-            .axis_pair = Some(default());
-        camera_actions.action_data_mut(CameraAction::Zoom).value = default();
+    for (mut camera_actions, input_map) in camera_actions_query.iter_mut() {
+        for action in CameraAction::variants() {
+            if !context.enables_camera_action(action.clone()) {
+                disable_action(&mut camera_actions, action);
+            }
+        }
+        let is_orbiting_with_mouse = input_map.map_or(false, |input_map| {
+            input_map
+                .get(CameraAction::Orbit)
+                .iter()
+                .any(is_mouse_motion)
+        });
+        if context.frees_cursor() && is_orbiting_with_mouse {
+            remove_mouse_motion(&mut camera_actions, CameraAction::Orbit, mouse_delta);
+        }
+    }
+}
+
+fn is_mouse_motion(input: &UserInput) -> bool {
+    match input {
+        UserInput::Single(InputKind::DualAxis(DualAxis { x, .. }))
+        | UserInput::Single(InputKind::SingleAxis(x)) => {
+            matches!(x.axis_type, AxisType::MouseMotion(_))
+        }
+        UserInput::Single(InputKind::MouseMotion(_)) => true,
+        _ => false,
+    }
+}
+
+/// An axis pair is the sum of all inputs bound to it, so this leaves only the other ones, e.g. a stick
+fn remove_mouse_motion<A: Actionlike>(actions: &mut ActionState<A>, action: A, mouse_delta: Vec2) {
+    let action_data = actions.action_data_mut(action);
+    let Some(axis_pair) = action_data.axis_pair else {
+        return;
+    };
+    let axis_pair = DualAxisData::from_xy(axis_pair.xy() - mouse_delta);
+    action_data.value = axis_pair.length();
+    action_data.axis_pair = Some(axis_pair);
+}
+
+fn disable_action<A: Actionlike>(actions: &mut ActionState<A>, action: A) {
+    actions.release(action.clone());
+    let action_data = actions.action_data_mut(action);
+    action_data.value = default();
+    if action_data.axis_pair.is_some() {
+        action_data.axis_pair = Some(default());
     }
 }
 
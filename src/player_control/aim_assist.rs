@@ -0,0 +1,210 @@
+use crate::combat::shoot::Shooting;
+use crate::file_system_interaction::config::{AimAssist, GameConfig};
+use crate::level_instantiation::spawning::objects::enemy::EnemyTag;
+use crate::player_control::camera::{CameraUpdateSystemSet, IngameCamera, IngameCameraKind};
+use crate::player_control::player_embodiment::Player;
+use crate::simulation::{InterpolationSystemSet, SimulationSet};
+use crate::util::trait_extension::Vec2Ext;
+use crate::GameState;
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far a stick needs to be pushed to count as using the gamepad
+const STICK_THRESHOLD: f32 = 0.2;
+
+/// Makes aiming with the right stick in first and third person less of a struggle, tuned in `camera.aim_assist` in the [`GameConfig`]:
+/// - The camera turns slower while the center of the screen is over an enemy, see [`IngameCamera::aim_assist_slowdown`].
+/// - Turning pulls the camera towards the nearest enemy close to the center of the screen, see [`IngameCamera::aim_assist_target`].
+/// - Shots that narrowly miss that enemy are bent towards it, see [`Shooting::assist_target`].
+///   This affects the simulation, so it is decided every tick from the simulated state and the recorded input device.
+///
+/// None of this happens while the player uses the mouse and keyboard, as tracked by [`ActiveInputDevice`].
+pub(crate) fn aim_assist_plugin(app: &mut App) {
+    app.register_type::<ActiveInputDevice>()
+        .init_resource::<ActiveInputDevice>()
+        .add_systems(
+            (track_input_device, update_aim_assist)
+                .chain()
+                .after(InterpolationSystemSet)
+                .before(CameraUpdateSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            update_bullet_bending
+                .in_set(SimulationSet::Input)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+}
+
+/// The device the player last used
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Resource, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) enum ActiveInputDevice {
+    #[default]
+    KeyboardAndMouse,
+    Gamepad,
+}
+
+fn track_input_device(
+    mut active_input_device: ResMut<ActiveInputDevice>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("track_input_device").entered();
+    let is_mouse_moved = mouse_motion
+        .iter()
+        .any(|motion| !motion.delta.is_approx_zero());
+    let is_keyboard_or_mouse_used = is_mouse_moved
+        || keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some();
+    let is_stick_moved = gamepads.iter().any(|gamepad| {
+        [
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+        ]
+        .into_iter()
+        .filter_map(|axis_type| gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+        .any(|value| value.abs() > STICK_THRESHOLD)
+    });
+    let is_gamepad_used = is_stick_moved || gamepad_buttons.get_just_pressed().next().is_some();
+
+    // Don't trigger change detection every frame
+    if is_gamepad_used && *active_input_device != ActiveInputDevice::Gamepad {
+        *active_input_device = ActiveInputDevice::Gamepad;
+    } else if is_keyboard_or_mouse_used
+        && !is_gamepad_used
+        && *active_input_device != ActiveInputDevice::KeyboardAndMouse
+    {
+        *active_input_device = ActiveInputDevice::KeyboardAndMouse;
+    }
+}
+
+fn update_aim_assist(
+    active_input_device: Res<ActiveInputDevice>,
+    player_query: Query<Entity, With<Player>>,
+    mut camera_query: Query<(&mut IngameCamera, &Transform), Without<Player>>,
+    enemies: Query<(Entity, &Transform), (With<EnemyTag>, Without<Player>)>,
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_aim_assist").entered();
+    let config = &config.camera.aim_assist;
+    for (mut camera, camera_transform) in camera_query.iter_mut() {
+        camera.aim_assist_slowdown = 1.0;
+        camera.aim_assist_target = None;
+        if !is_assisted(*active_input_device, &camera) {
+            continue;
+        }
+        for player in player_query.iter() {
+            let filter = QueryFilter::new()
+                .exclude_collider(player)
+                .exclude_sensors();
+            if config.slowdown_enabled {
+                let hit = rapier_context.cast_ray(
+                    camera_transform.translation,
+                    camera_transform.forward(),
+                    config.max_distance,
+                    true,
+                    filter,
+                );
+                if let Some((entity, _toi)) = hit && enemies.contains(entity) {
+                    camera.aim_assist_slowdown = config.slowdown_factor;
+                }
+            }
+            if config.magnetism_enabled {
+                let max_angle = config.magnetism_angle_degrees.to_radians();
+                camera.aim_assist_target = find_nearest_enemy(
+                    camera_transform,
+                    max_angle,
+                    filter,
+                    &enemies,
+                    &rapier_context,
+                    config,
+                );
+            }
+        }
+    }
+}
+
+/// Reads the camera's [`Transform`] at the start of the tick, which is recorded in replays
+fn update_bullet_bending(
+    active_input_device: Res<ActiveInputDevice>,
+    mut player_query: Query<(Entity, &mut Shooting), With<Player>>,
+    camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
+    enemies: Query<(Entity, &Transform), (With<EnemyTag>, Without<Player>)>,
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_bullet_bending").entered();
+    let config = &config.camera.aim_assist;
+    let Some((camera, camera_transform)) = camera_query.iter().next() else {
+        return;
+    };
+    for (player, mut shooting) in player_query.iter_mut() {
+        shooting.assist_target = None;
+        if !config.bullet_bending_enabled || !is_assisted(*active_input_device, camera) {
+            continue;
+        }
+        let filter = QueryFilter::new()
+            .exclude_collider(player)
+            .exclude_sensors();
+        let max_angle = config.bullet_bending_angle_degrees.to_radians();
+        shooting.assist_target = find_nearest_enemy(
+            camera_transform,
+            max_angle,
+            filter,
+            &enemies,
+            &rapier_context,
+            config,
+        );
+    }
+}
+
+/// Top-down aiming has its own reticle, and a lock-on already keeps the enemy in view
+fn is_assisted(active_input_device: ActiveInputDevice, camera: &IngameCamera) -> bool {
+    active_input_device == ActiveInputDevice::Gamepad
+        && camera.kind != IngameCameraKind::FixedAngle
+        && !camera.locked_on
+}
+
+/// The visible enemy closest to the center of the screen, if it is within `max_angle` of it
+fn find_nearest_enemy(
+    camera_transform: &Transform,
+    max_angle: f32,
+    filter: QueryFilter,
+    enemies: &Query<(Entity, &Transform), (With<EnemyTag>, Without<Player>)>,
+    rapier_context: &RapierContext,
+    config: &AimAssist,
+) -> Option<Vec3> {
+    let origin = camera_transform.translation;
+    let forward = camera_transform.forward();
+    enemies
+        .iter()
+        .filter_map(|(enemy, transform)| {
+            let to_enemy = transform.translation - origin;
+            let distance = to_enemy.length();
+            let direction = to_enemy.try_normalize()?;
+            let angle = direction.angle_between(forward);
+            if distance > config.max_distance || angle > max_angle {
+                return None;
+            }
+            let hit = rapier_context.cast_ray(origin, direction, distance, true, filter);
+            let is_visible = hit.map_or(true, |(entity, _toi)| entity == enemy);
+            is_visible.then_some((transform.translation, angle))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(position, _angle)| position)
+}
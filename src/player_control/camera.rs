@@ -1,20 +1,38 @@
 use crate::player_control::camera::kind::update_drivers;
 use crate::player_control::camera::{
-    cursor::grab_cursor, focus::set_camera_focus, kind::update_kind, rig::update_rig,
+    cinematic::{play_cinematic, read_camera_paths, start_cinematic, trigger_level_cinematics},
+    cursor::grab_cursor,
+    focus::set_camera_focus,
+    kind::update_kind,
+    photo_mode::{fly_photo_camera, show_photo_mode_ui, toggle_photo_mode},
+    rig::update_rig,
+    shake::{shake_camera, shake_on_damage, shake_on_landing},
     skydome::move_skydome,
+    tilt::tilt_camera,
 };
+use crate::simulation::InterpolationSystemSet;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_dolly::prelude::*;
+use screenshot::screenshot_plugin;
+use seldom_fn_plugin::FnPluginExt;
+pub(crate) use cinematic::{ActiveCinematic, Cinematic, CinematicEvent};
 pub(crate) use cursor::ForceCursorGrabMode;
+pub(crate) use photo_mode::{is_hud_visible, PhotoMode};
+pub(crate) use shake::CameraShake;
 use serde::{Deserialize, Serialize};
 use ui::*;
 
+mod cinematic;
 mod cursor;
 pub(crate) mod focus;
 mod kind;
+mod photo_mode;
 mod rig;
+mod screenshot;
+mod shake;
 mod skydome;
+mod tilt;
 mod ui;
 
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, FromReflect)]
@@ -24,6 +42,19 @@ pub(crate) struct IngameCamera {
     pub(crate) secondary_target: Option<Transform>,
     pub(crate) desired_distance: f32,
     pub(crate) kind: IngameCameraKind,
+    /// Whether [`IngameCamera::secondary_target`] is an enemy to keep in view while orbiting the player,
+    /// as opposed to a dialog partner to orbit instead
+    pub(crate) locked_on: bool,
+    /// Current roll in degrees, e.g. while wall-running
+    pub(crate) tilt_degrees: f32,
+    /// How far the camera has zoomed in for aiming down sights, from 0 (not at all) to 1 (fully)
+    pub(crate) aim_zoom: f32,
+    /// Current field of view divided by the one we would have without aiming. Scales the mouse sensitivity.
+    pub(crate) zoom_ratio: f32,
+    /// Factor applied to the sensitivity by gamepad aim assist, 1 when not slowed down
+    pub(crate) aim_assist_slowdown: f32,
+    /// Enemy that gamepad aim assist pulls the camera towards while turning
+    pub(crate) aim_assist_target: Option<Vec3>,
 }
 
 impl Default for IngameCamera {
@@ -33,6 +64,12 @@ impl Default for IngameCamera {
             target: default(),
             secondary_target: default(),
             kind: default(),
+            locked_on: false,
+            tilt_degrees: 0.,
+            aim_zoom: 0.,
+            zoom_ratio: 1.,
+            aim_assist_slowdown: 1.,
+            aim_assist_target: None,
         }
     }
 }
@@ -49,12 +86,24 @@ pub(crate) enum IngameCameraKind {
 /// Handles the main ingame camera, i.e. not the UI camera in the menu.
 /// Cameras are controlled with [`CameraActions`]. Depending on the distance, a first person,
 /// third person or fixed angle camera is used.
+/// On top of that, the game can be frozen and the camera flown around freely in [`PhotoMode`],
+/// and levels and dialogs can take over the camera for a [`Cinematic`].
 pub(crate) fn camera_plugin(app: &mut App) {
     app.register_type::<UiCamera>()
         .register_type::<IngameCamera>()
         .register_type::<IngameCameraKind>()
+        .register_type::<CameraShake>()
+        .register_type::<PhotoMode>()
+        .register_type::<ActiveCinematic>()
+        .register_type::<cinematic::CameraPathNode>()
+        .register_type::<cinematic::CameraLookAt>()
+        .register_type::<cinematic::CinematicTrigger>()
+        .add_event::<CinematicEvent>()
         .init_resource::<ForceCursorGrabMode>()
-        .add_system(Dolly::<IngameCamera>::update_active)
+        .init_resource::<CameraShake>()
+        .fn_plugin(screenshot_plugin)
+        .configure_set(CameraUpdateSystemSet.run_if(not(is_camera_detached)))
+        .add_system(Dolly::<IngameCamera>::update_active.run_if(not(is_camera_detached)))
         .add_system(spawn_ui_camera.on_startup())
         .add_system(despawn_ui_camera.in_schedule(OnEnter(GameState::Playing)))
         .add_system(grab_cursor.in_set(OnUpdate(GameState::Playing)))
@@ -67,10 +116,54 @@ pub(crate) fn camera_plugin(app: &mut App) {
                 move_skydome,
             )
                 .chain()
+                .after(InterpolationSystemSet)
                 .in_set(CameraUpdateSystemSet)
                 .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                tilt_camera.run_if(not(is_camera_detached)),
+                shake_camera.run_if(not(resource_exists::<PhotoMode>())),
+            )
+                .chain()
+                .after(Dolly::<IngameCamera>::update_active)
+                .after(CameraUpdateSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (shake_on_landing, shake_on_damage)
+                .before(shake_camera)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(toggle_photo_mode.in_set(OnUpdate(GameState::Playing)))
+        .add_systems(
+            (
+                fly_photo_camera.run_if(resource_exists::<PhotoMode>()),
+                show_photo_mode_ui.run_if(resource_exists::<PhotoMode>()),
+            )
+                .after(toggle_photo_mode)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                read_camera_paths,
+                trigger_level_cinematics,
+                start_cinematic,
+                play_cinematic.run_if(resource_exists::<ActiveCinematic>()),
+            )
+                .chain()
+                .before(shake_camera)
+                .in_set(OnUpdate(GameState::Playing)),
         );
 }
 
+/// Whether the camera has been taken over by [`PhotoMode`] or a [`Cinematic`], in which case the rig leaves it alone
+pub(crate) fn is_camera_detached(
+    photo_mode: Option<Res<PhotoMode>>,
+    active_cinematic: Option<Res<ActiveCinematic>>,
+) -> bool {
+    photo_mode.is_some() || active_cinematic.is_some()
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub(crate) struct CameraUpdateSystemSet;
@@ -0,0 +1,256 @@
+use crate::player_control::actions::{InputContext, InputContextStack};
+use crate::player_control::camera::IngameCamera;
+use crate::util::trait_extension::Vec3Ext;
+use crate::world_interaction::condition::{ConditionAddEvent, ConditionId};
+use crate::world_interaction::dialog::{DialogEvent, DialogId};
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// A scripted camera flight, e.g. for a level intro or a boss reveal.
+/// The camera follows a spline through the level nodes tagged `[camera_path:<path>]`, visited in the alphabetical order of their names.
+/// Start one by sending a [`CinematicEvent`], ending a dialog page that has one, or tagging any level node with
+/// `[play_cinematic:<path>:<seconds>]`, which plays it when the level is loaded.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct Cinematic {
+    pub(crate) path: String,
+    pub(crate) duration_seconds: f32,
+    #[serde(default)]
+    pub(crate) easing: Easing,
+    /// Name of the `[camera_look_at:<name>]` node to keep in view. Defaults to the one named like the path.
+    /// Without such a node, the camera looks where it is going.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) look_at: Option<String>,
+    /// Dialog started when the flight is over. Its speaker is the look-at node, or the last node of the path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) dialog_on_end: Option<DialogId>,
+    /// Condition added when the flight is over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) condition_on_end: Option<ConditionId>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub(crate) struct CinematicEvent(pub(crate) Cinematic);
+
+/// Present while a [`Cinematic`] is playing. The [`IngameCamera`] is detached from its rig until then,
+/// after which the regular camera that was spawned for the level takes over again.
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct ActiveCinematic {
+    pub(crate) cinematic: Cinematic,
+    pub(crate) elapsed_seconds: f32,
+}
+
+/// A control point of the camera paths with the given name
+#[derive(Debug, Clone, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct CameraPathNode(pub(crate) String);
+
+/// Something for the camera to look at during cinematics with the given name
+#[derive(Debug, Clone, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct CameraLookAt(pub(crate) String);
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct CinematicTrigger(pub(crate) Cinematic);
+
+static CAMERA_PATH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[camera_path:\s*(\w+)\]").expect("Failed to compile camera path regex")
+});
+
+static CAMERA_LOOK_AT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[camera_look_at:\s*(\w+)\]").expect("Failed to compile camera look at regex")
+});
+
+static PLAY_CINEMATIC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[play_cinematic:\s*(\w+)\s*:\s*(\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile play cinematic regex")
+});
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn read_camera_paths(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name), Added<Name>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_camera_paths").entered();
+    for (entity, name) in &added_name {
+        let name = name.to_lowercase();
+        let Some(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+        if let Some(captures) = CAMERA_PATH_REGEX.captures(&name) {
+            entity_commands.insert(CameraPathNode(captures[1].to_string()));
+        }
+        if let Some(captures) = CAMERA_LOOK_AT_REGEX.captures(&name) {
+            entity_commands.insert(CameraLookAt(captures[1].to_string()));
+        }
+        if let Some(captures) = PLAY_CINEMATIC_REGEX.captures(&name) {
+            let duration_seconds = captures[2]
+                .parse()
+                .with_context(|| format!("Failed to parse cinematic duration of {name}"))?;
+            entity_commands.insert(CinematicTrigger(Cinematic {
+                path: captures[1].to_string(),
+                duration_seconds,
+                ..default()
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Runs a frame after [`read_camera_paths`] so that all nodes of the level have been tagged by then
+pub(crate) fn trigger_level_cinematics(
+    triggers: Query<&CinematicTrigger, Added<CinematicTrigger>>,
+    mut cinematic_events: EventWriter<CinematicEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("trigger_level_cinematics").entered();
+    for trigger in &triggers {
+        cinematic_events.send(CinematicEvent(trigger.0.clone()));
+    }
+}
+
+pub(crate) fn start_cinematic(
+    mut commands: Commands,
+    mut cinematic_events: EventReader<CinematicEvent>,
+    active_cinematic: Option<Res<ActiveCinematic>>,
+    mut input_contexts: ResMut<InputContextStack>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_cinematic").entered();
+    // Only the latest request matters
+    let Some(CinematicEvent(cinematic)) = cinematic_events.iter().last() else {
+        return;
+    };
+    if active_cinematic.is_none() {
+        input_contexts.push(InputContext::Spectator);
+    }
+    commands.insert_resource(ActiveCinematic {
+        cinematic: cinematic.clone(),
+        elapsed_seconds: 0.0,
+    });
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn play_cinematic(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut active_cinematic: ResMut<ActiveCinematic>,
+    path_nodes: Query<(Entity, &Name, &CameraPathNode, &GlobalTransform)>,
+    look_at_nodes: Query<(Entity, &CameraLookAt, &GlobalTransform)>,
+    mut camera_query: Query<&mut Transform, With<IngameCamera>>,
+    mut input_contexts: ResMut<InputContextStack>,
+    mut dialog_events: EventWriter<DialogEvent>,
+    mut condition_events: EventWriter<ConditionAddEvent>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_cinematic").entered();
+    let cinematic = active_cinematic.cinematic.clone();
+    let mut nodes: Vec<_> = path_nodes
+        .iter()
+        .filter(|(_, _, node, _)| node.0 == cinematic.path)
+        .map(|(entity, name, _, transform)| (name.as_str(), entity, transform.translation()))
+        .collect();
+    nodes.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    let Some(&(_, last_node, _)) = nodes.last() else {
+        commands.remove_resource::<ActiveCinematic>();
+        input_contexts.pop(InputContext::Spectator);
+        bail!("No camera path named \"{}\" in the current level", cinematic.path);
+    };
+    let points: Vec<_> = nodes.iter().map(|(_, _, point)| *point).collect();
+    let look_at_name = cinematic.look_at.as_ref().unwrap_or(&cinematic.path);
+    let look_at = look_at_nodes
+        .iter()
+        .find(|(_, look_at, _)| &look_at.0 == look_at_name)
+        .map(|(entity, _, transform)| (entity, transform.translation()));
+
+    active_cinematic.elapsed_seconds += time.delta_seconds();
+    let progress =
+        (active_cinematic.elapsed_seconds / cinematic.duration_seconds.max(1e-5)).min(1.0);
+    let t = cinematic.easing.apply(progress);
+    let position = sample_path(&points, t);
+    let target = look_at
+        .map(|(_, target)| target)
+        .unwrap_or_else(|| position + get_path_direction(&points, t));
+    for mut transform in camera_query.iter_mut() {
+        transform.translation = position;
+        if !(target - position).is_approx_zero() {
+            transform.look_at(target, Vec3::Y);
+        }
+    }
+
+    if progress < 1.0 {
+        return Ok(());
+    }
+    commands.remove_resource::<ActiveCinematic>();
+    input_contexts.pop(InputContext::Spectator);
+    if let Some(condition) = cinematic.condition_on_end {
+        condition_events.send(ConditionAddEvent(condition));
+    }
+    if let Some(dialog) = cinematic.dialog_on_end {
+        let source = look_at.map(|(entity, _)| entity).unwrap_or(last_node);
+        dialog_events.send(DialogEvent {
+            dialog,
+            source,
+            page: None,
+        });
+    }
+    Ok(())
+}
+
+/// Samples a Catmull-Rom spline through all points at `t` in [0, 1]
+fn sample_path(points: &[Vec3], t: f32) -> Vec3 {
+    if points.len() < 2 {
+        return points[0];
+    }
+    let segments = (points.len() - 1) as f32;
+    let scaled = t.clamp(0.0, 1.0) * segments;
+    let index = (scaled.floor() as usize).min(points.len() - 2);
+    let local_t = scaled - index as f32;
+    let get = |i: isize| points[i.clamp(0, points.len() as isize - 1) as usize];
+    let index = index as isize;
+    let (p0, p1, p2, p3) = (get(index - 1), get(index), get(index + 1), get(index + 2));
+    let t2 = local_t * local_t;
+    let t3 = t2 * local_t;
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * local_t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn get_path_direction(points: &[Vec3], t: f32) -> Vec3 {
+    const EPSILON: f32 = 1e-3;
+    let start = sample_path(points, (t - EPSILON).max(0.0));
+    let end = sample_path(points, (t + EPSILON).min(1.0));
+    (end - start).try_normalize().unwrap_or(Vec3::NEG_Z)
+}
@@ -1,4 +1,5 @@
-use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::actions::InputContextStack;
+use crate::player_control::camera::{IngameCamera, IngameCameraKind};
 use anyhow::{Context, Result};
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, PrimaryWindow};
@@ -11,8 +12,9 @@ pub(crate) struct ForceCursorGrabMode(pub(crate) Option<CursorGrabMode>);
 #[sysfail(log(level = "error"))]
 pub(crate) fn grab_cursor(
     mut primary_windows: Query<&mut Window, With<PrimaryWindow>>,
-    actions_frozen: Res<ActionsFrozen>,
+    input_contexts: Res<InputContextStack>,
     force_cursor_grab: Res<ForceCursorGrabMode>,
+    camera_query: Query<&IngameCamera>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("cursor_grab_system").entered();
@@ -20,12 +22,19 @@ pub(crate) fn grab_cursor(
         .get_single_mut()
         .context("Failed to get primary window")?;
     let cursor = &mut window.cursor;
+    // Top-down aiming points at the cursor, so it needs to stay visible
+    let is_aiming_with_cursor = camera_query
+        .iter()
+        .any(|camera| camera.kind == IngameCameraKind::FixedAngle);
     if let Some(mode) = force_cursor_grab.0 {
         cursor.grab_mode = mode;
         cursor.visible = mode != CursorGrabMode::Locked;
-    } else if actions_frozen.is_frozen() {
+    } else if input_contexts.current().frees_cursor() {
         cursor.grab_mode = CursorGrabMode::None;
         cursor.visible = true;
+    } else if is_aiming_with_cursor {
+        cursor.grab_mode = CursorGrabMode::Confined;
+        cursor.visible = true;
     } else {
         cursor.grab_mode = CursorGrabMode::Locked;
         cursor.visible = false;
@@ -1,4 +1,5 @@
 use crate::player_control::camera::IngameCamera;
+use crate::player_control::lock_on::LockOn;
 use crate::player_control::player_embodiment::Player;
 use crate::world_interaction::dialog::CurrentDialog;
 use anyhow::Result;
@@ -9,16 +10,21 @@ use bevy_mod_sysfail::macros::*;
 pub(crate) fn set_camera_focus(
     mut camera_query: Query<&mut IngameCamera>,
     current_dialog: Option<Res<CurrentDialog>>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(&Transform, Option<&LockOn>), With<Player>>,
     non_player_query: Query<&GlobalTransform, Without<Player>>,
 ) -> Result<()> {
     for mut camera in camera_query.iter_mut() {
-        for player_transform in player_query.iter() {
+        for (player_transform, lock_on) in player_query.iter() {
+            camera.locked_on = false;
             if let Some(ref active_dialogue) = current_dialog {
                 let dialog_target_transform = non_player_query
                     .get(active_dialogue.source)?
                     .compute_transform();
                 camera.secondary_target = Some(dialog_target_transform);
+            } else if let Some(target) = lock_on.and_then(|lock_on| lock_on.target) {
+                let lock_on_target_transform = non_player_query.get(target)?.compute_transform();
+                camera.secondary_target = Some(lock_on_target_transform);
+                camera.locked_on = true;
             } else {
                 camera.secondary_target = None;
             }
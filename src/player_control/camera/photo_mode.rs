@@ -0,0 +1,204 @@
+use crate::player_control::actions::{InputContext, InputContextStack, UiAction};
+use crate::player_control::camera::screenshot::ScreenshotRequest;
+use crate::player_control::camera::{ActiveCinematic, IngameCamera};
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::render::view::ColorGrading;
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::ops::DerefMut;
+
+/// Radians the camera turns per pixel of mouse movement
+const LOOK_SENSITIVITY: f32 = 3e-3;
+/// Factor applied to the flying speed while holding shift
+const FAST_FLIGHT_FACTOR: f32 = 4.0;
+
+/// Present while the game is frozen in photo mode. The [`IngameCamera`] is then detached from its rig and flies freely,
+/// while [`IngameCamera::kind`] keeps what it was, so that the regular camera continues where it left off afterwards.
+/// Screenshots are taken with a [`ScreenshotRequest`] and leave out the photo mode UI.
+/// Note that there is no depth of field setting, since Bevy 0.10 has no depth of field effect to drive.
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct PhotoMode {
+    /// Vertical field of view in radians
+    pub(crate) fov: f32,
+    /// What the field of view was before entering photo mode
+    pub(crate) previous_fov: f32,
+    /// Exposure offset in stops, see [`ColorGrading::exposure`]
+    pub(crate) exposure: f32,
+    /// What the exposure was before entering photo mode
+    pub(crate) previous_exposure: f32,
+    /// Flying speed in m/s
+    pub(crate) speed: f32,
+    pub(crate) hide_hud: bool,
+}
+
+impl Default for PhotoMode {
+    fn default() -> Self {
+        Self {
+            fov: std::f32::consts::FRAC_PI_4,
+            previous_fov: std::f32::consts::FRAC_PI_4,
+            exposure: 0.0,
+            previous_exposure: 0.0,
+            speed: 5.0,
+            hide_hud: false,
+        }
+    }
+}
+
+/// Run condition for systems that draw the HUD. It is also hidden during cinematics.
+pub(crate) fn is_hud_visible(
+    photo_mode: Option<Res<PhotoMode>>,
+    active_cinematic: Option<Res<ActiveCinematic>>,
+) -> bool {
+    photo_mode.map_or(true, |photo_mode| !photo_mode.hide_hud) && active_cinematic.is_none()
+}
+
+pub(crate) fn toggle_photo_mode(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    actions: Query<&ActionState<UiAction>>,
+    mut input_contexts: ResMut<InputContextStack>,
+    photo_mode: Option<Res<PhotoMode>>,
+    active_cinematic: Option<Res<ActiveCinematic>>,
+    mut cameras: Query<(&mut Projection, &mut ColorGrading), With<IngameCamera>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("toggle_photo_mode").entered();
+    if !actions
+        .iter()
+        .any(|action| action.just_pressed(UiAction::TogglePhotoMode))
+    {
+        return;
+    }
+    if let Some(photo_mode) = photo_mode {
+        for (mut projection, mut color_grading) in cameras.iter_mut() {
+            if let Projection::Perspective(ref mut perspective) = projection.deref_mut() {
+                perspective.fov = photo_mode.previous_fov;
+            }
+            color_grading.exposure = photo_mode.previous_exposure;
+        }
+        commands.remove_resource::<PhotoMode>();
+        time.unpause();
+        input_contexts.pop(InputContext::Menu);
+    } else if !time.is_paused() && active_cinematic.is_none() {
+        // Don't interfere with the pause menu or a cinematic
+        let fov = cameras
+            .iter()
+            .find_map(|(projection, _color_grading)| match projection {
+                Projection::Perspective(perspective) => Some(perspective.fov),
+                _ => None,
+            })
+            .unwrap_or(std::f32::consts::FRAC_PI_4);
+        let exposure = cameras
+            .iter()
+            .next()
+            .map_or(0.0, |(_projection, color_grading)| color_grading.exposure);
+        commands.insert_resource(PhotoMode {
+            fov,
+            previous_fov: fov,
+            exposure,
+            previous_exposure: exposure,
+            ..default()
+        });
+        time.pause();
+        input_contexts.push(InputContext::Menu);
+    }
+}
+
+pub(crate) fn fly_photo_camera(
+    time: Res<Time>,
+    photo_mode: Res<PhotoMode>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut camera_query: Query<
+        (&mut Transform, &mut Projection, &mut ColorGrading),
+        With<IngameCamera>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("fly_photo_camera").entered();
+    // The game time is paused, but the real time keeps going
+    let dt = time.raw_delta_seconds();
+    let mouse_delta: Vec2 = mouse_motion.iter().map(|motion| motion.delta).sum();
+    // The cursor is free so that the sliders can be used, so only look around while the right mouse button is held
+    let is_looking = mouse_buttons.pressed(MouseButton::Right);
+
+    for (mut transform, mut projection, mut color_grading) in camera_query.iter_mut() {
+        if let Projection::Perspective(ref mut perspective) = projection.deref_mut() {
+            perspective.fov = photo_mode.fov;
+        }
+        color_grading.exposure = photo_mode.exposure;
+        if is_looking {
+            let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+            let yaw = yaw - mouse_delta.x * LOOK_SENSITIVITY;
+            let pitch = (pitch - mouse_delta.y * LOOK_SENSITIVITY).clamp(-1.54, 1.54);
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+        }
+
+        let mut direction = Vec3::ZERO;
+        for (key, key_direction) in [
+            (KeyCode::W, transform.forward()),
+            (KeyCode::S, transform.back()),
+            (KeyCode::A, transform.left()),
+            (KeyCode::D, transform.right()),
+            (KeyCode::E, Vec3::Y),
+            (KeyCode::Q, Vec3::NEG_Y),
+        ] {
+            if keys.pressed(key) {
+                direction += key_direction;
+            }
+        }
+        let speed = if keys.pressed(KeyCode::LShift) {
+            photo_mode.speed * FAST_FLIGHT_FACTOR
+        } else {
+            photo_mode.speed
+        };
+        transform.translation += direction.normalize_or_zero() * speed * dt;
+    }
+}
+
+pub(crate) fn show_photo_mode_ui(
+    mut egui_contexts: EguiContexts,
+    mut photo_mode: ResMut<PhotoMode>,
+    keys: Res<Input<KeyCode>>,
+    mut screenshot_requests: EventWriter<ScreenshotRequest>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_photo_mode_ui").entered();
+    if keys.just_pressed(KeyCode::H) {
+        photo_mode.hide_hud = !photo_mode.hide_hud;
+    }
+    if keys.just_pressed(KeyCode::F12) {
+        screenshot_requests.send(ScreenshotRequest);
+    }
+    if photo_mode.hide_hud {
+        return;
+    }
+    egui::Window::new("Photo Mode")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10., 10.))
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.add(
+                egui::Slider::new(&mut photo_mode.fov, 0.2..=2.0)
+                    .text("Field of view")
+                    .custom_formatter(|fov, _| format!("{:.0}°", fov.to_degrees())),
+            );
+            ui.add(
+                egui::Slider::new(&mut photo_mode.exposure, -4.0..=4.0)
+                    .text("Exposure")
+                    .custom_formatter(|exposure, _| format!("{exposure:+.1} EV")),
+            );
+            ui.add(egui::Slider::new(&mut photo_mode.speed, 0.5..=20.0).text("Speed"));
+            ui.checkbox(&mut photo_mode.hide_hud, "Hide HUD (H)");
+            if ui.button("Take screenshot (F12)").clicked() {
+                screenshot_requests.send(ScreenshotRequest);
+            }
+            ui.separator();
+            ui.label("WASD / Q / E: fly, hold Shift to go faster");
+            ui.label("Hold right mouse button: look around");
+            ui.label("P: back to the game");
+        });
+}
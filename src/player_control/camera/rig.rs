@@ -2,7 +2,7 @@ use crate::file_system_interaction::config::GameConfig;
 use crate::player_control::actions::CameraAction;
 use crate::player_control::camera::rig::arm::{get_arm_distance, get_zoom_smoothness, set_arm};
 use crate::player_control::camera::{IngameCamera, IngameCameraKind};
-use crate::util::trait_extension::Vec2Ext;
+use crate::util::trait_extension::{F32Ext, Vec2Ext};
 use anyhow::{Context, Result};
 use bevy::prelude::*;
 use bevy_dolly::prelude::*;
@@ -28,14 +28,18 @@ pub(crate) fn update_rig(
     for (mut camera, mut rig, actions, transform) in camera_query.iter_mut() {
         set_look_at(&mut rig, &camera);
         set_position(&mut rig, &camera);
+        set_shoulder_offset(&mut rig, &camera, transform, &config);
         if camera.kind == IngameCameraKind::FixedAngle {
             let yaw_pitch = rig.driver_mut::<YawPitch>();
             yaw_pitch.yaw_degrees = 0.;
             yaw_pitch.pitch_degrees = config.camera.fixed_angle.pitch;
+        } else if camera.locked_on && let Some(secondary_target) = camera.secondary_target {
+            face_secondary_target(&mut rig, &camera, secondary_target);
         } else {
             let camera_movement = get_camera_movement(actions)?;
             if !camera_movement.is_approx_zero() {
                 set_yaw_pitch(&mut rig, &camera, camera_movement, &config);
+                pull_towards_aim_assist_target(&mut rig, &camera, transform, &config, dt);
             }
         }
 
@@ -60,13 +64,50 @@ fn get_camera_movement(actions: &ActionState<CameraAction>) -> Result<Vec2> {
 
 fn set_yaw_pitch(rig: &mut Rig, camera: &IngameCamera, camera_movement: Vec2, config: &GameConfig) {
     let yaw_pitch = rig.driver_mut::<YawPitch>();
-    let yaw = -camera_movement.x * config.camera.mouse_sensitivity_x;
-    let pitch = -camera_movement.y * config.camera.mouse_sensitivity_y;
+    // Zooming in makes everything move faster on screen, so slow the camera down by the same amount
+    let sensitivity_factor = camera.zoom_ratio * camera.aim_assist_slowdown;
+    let yaw = -camera_movement.x * config.camera.mouse_sensitivity_x * sensitivity_factor;
+    let pitch = -camera_movement.y * config.camera.mouse_sensitivity_y * sensitivity_factor;
     yaw_pitch.rotate_yaw_pitch(yaw.to_degrees(), pitch.to_degrees());
     let (min_pitch, max_pitch) = get_pitch_extrema(config, camera);
     yaw_pitch.pitch_degrees = yaw_pitch.pitch_degrees.clamp(min_pitch, max_pitch);
 }
 
+/// Gamepad aim assist magnetism. Only called while the player is turning, so that the camera never moves by itself.
+fn pull_towards_aim_assist_target(
+    rig: &mut Rig,
+    camera: &IngameCamera,
+    transform: &Transform,
+    config: &GameConfig,
+    dt: f32,
+) {
+    let Some(target) = camera.aim_assist_target else {
+        return;
+    };
+    let Some(direction) = (target - transform.translation).try_normalize() else {
+        return;
+    };
+    let target_yaw = (-direction.x).atan2(-direction.z).to_degrees();
+    let target_pitch = direction.y.asin().to_degrees();
+    let ratio = (config.camera.aim_assist.magnetism_strength * dt).min(1.0);
+    let yaw_pitch = rig.driver_mut::<YawPitch>();
+    // Take the short way around
+    let yaw_difference = (target_yaw - yaw_pitch.yaw_degrees + 180.0).rem_euclid(360.0) - 180.0;
+    let pitch_difference = target_pitch - yaw_pitch.pitch_degrees;
+    yaw_pitch.rotate_yaw_pitch(yaw_difference * ratio, pitch_difference * ratio);
+    let (min_pitch, max_pitch) = get_pitch_extrema(config, camera);
+    yaw_pitch.pitch_degrees = yaw_pitch.pitch_degrees.clamp(min_pitch, max_pitch);
+}
+
+/// Turns the camera so that it looks past the player at the target, instead of following the orbit input.
+fn face_secondary_target(rig: &mut Rig, camera: &IngameCamera, secondary_target: Transform) {
+    let direction = secondary_target.translation - camera.target.translation;
+    if direction.x.is_approx_zero() && direction.z.is_approx_zero() {
+        return;
+    }
+    rig.driver_mut::<YawPitch>().yaw_degrees = (-direction.x).atan2(-direction.z).to_degrees();
+}
+
 fn set_look_at(rig: &mut Rig, camera: &IngameCamera) {
     if let Some(look_at) = rig.try_driver_mut::<LookAt>() {
         if let Some(secondary_target) = camera.secondary_target {
@@ -78,7 +119,7 @@ fn set_look_at(rig: &mut Rig, camera: &IngameCamera) {
 }
 
 fn set_position(rig: &mut Rig, camera: &IngameCamera) {
-    let target = if camera.kind != IngameCameraKind::FirstPerson && let Some(secondary_target) = camera.secondary_target {
+    let target = if camera.kind != IngameCameraKind::FirstPerson && !camera.locked_on && let Some(secondary_target) = camera.secondary_target {
         secondary_target.translation
     } else {
         camera.target.translation
@@ -86,6 +127,28 @@ fn set_position(rig: &mut Rig, camera: &IngameCamera) {
     rig.driver_mut::<Position>().position = target;
 }
 
+/// Moves the camera over the player's shoulder while aiming down sights in third person,
+/// so that the player does not cover what they are aiming at.
+fn set_shoulder_offset(
+    rig: &mut Rig,
+    camera: &IngameCamera,
+    transform: &Transform,
+    config: &GameConfig,
+) {
+    let offset = match camera.kind {
+        IngameCameraKind::ThirdPerson => {
+            config.camera.third_person.aim_shoulder_offset * camera.aim_zoom
+        }
+        _ => 0.0,
+    };
+    if let Some(arm) = rig.try_driver_mut::<Arm>() {
+        arm.offset.x = offset;
+    }
+    if camera.secondary_target.is_none() && let Some(look_at) = rig.try_driver_mut::<LookAt>() {
+        look_at.target += transform.right() * offset;
+    }
+}
+
 fn get_pitch_extrema(config: &GameConfig, camera: &IngameCamera) -> (f32, f32) {
     match camera.kind {
         IngameCameraKind::ThirdPerson => (
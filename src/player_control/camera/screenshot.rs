@@ -0,0 +1,295 @@
+use crate::player_control::camera::IngameCamera;
+use anyhow::{Context, Result};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    ImageDataLayout, Maintain, MapMode, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages,
+};
+use bevy::render::renderer::{render_system, RenderDevice, RenderQueue};
+use bevy::render::view::ColorGrading;
+use bevy::render::{Extract, ExtractSchedule, RenderApp, RenderSet};
+use bevy::window::PrimaryWindow;
+use bevy_mod_sysfail::macros::*;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+/// The screenshot image uses [`TextureFormat::Rgba8UnormSrgb`]
+const BYTES_PER_PIXEL: usize = 4;
+/// wgpu needs every row of a texture copied into a buffer to start at a multiple of this
+const COPY_BYTES_PER_ROW_ALIGNMENT: usize = 256;
+/// Frames the screenshot camera renders before it is read back, so that its image is ready on the GPU
+const SCREENSHOT_WARMUP_FRAMES: u8 = 2;
+
+/// Takes screenshots of what the [`IngameCamera`] sees, without any UI on top, when a [`ScreenshotRequest`] is sent.
+/// Bevy 0.10 cannot read back what is shown in the window, so a copy of the camera renders into an image instead.
+/// The render world copies that image from the GPU and sends it back, where it is written to `screenshots/<date and time>.png`.
+pub(crate) fn screenshot_plugin(app: &mut App) {
+    let (sender, receiver) = mpsc::channel();
+    app.add_event::<ScreenshotRequest>()
+        .insert_resource(CapturedScreenshots(Mutex::new(receiver)))
+        .add_systems(
+            (
+                advance_screenshot_cameras,
+                spawn_screenshot_camera,
+                save_screenshots,
+            )
+                .chain(),
+        );
+    if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .insert_resource(ScreenshotSender(Mutex::new(sender)))
+            .add_system(extract_screenshot_cameras.in_schedule(ExtractSchedule))
+            .add_system(
+                read_back_screenshots
+                    .after(render_system)
+                    .in_set(RenderSet::Render),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ScreenshotRequest;
+
+/// Renders a single screenshot into [`ScreenshotCamera::image`]
+#[derive(Debug, Clone, PartialEq, Component)]
+struct ScreenshotCamera {
+    image: Handle<Image>,
+    path: PathBuf,
+    /// Once this reaches zero, the image is read back and the camera despawned on the next frame
+    frames_left: u8,
+}
+
+/// Screenshots that are read back this frame, extracted into the render world
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+struct PendingScreenshots(Vec<(Handle<Image>, PathBuf)>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Screenshot {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    /// Tightly packed RGBA rows
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Resource)]
+struct ScreenshotSender(Mutex<Sender<Screenshot>>);
+
+#[derive(Debug, Resource)]
+struct CapturedScreenshots(Mutex<Receiver<Screenshot>>);
+
+fn advance_screenshot_cameras(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut ScreenshotCamera)>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("advance_screenshot_cameras").entered();
+    for (entity, mut camera) in cameras.iter_mut() {
+        if camera.frames_left == 0 {
+            images.remove(&camera.image);
+            commands.entity(entity).despawn_recursive();
+        } else {
+            camera.frames_left -= 1;
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn spawn_screenshot_camera(
+    mut commands: Commands,
+    mut screenshot_requests: EventReader<ScreenshotRequest>,
+    primary_windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<
+        (&GlobalTransform, &Projection, &ColorGrading, &Tonemapping),
+        With<IngameCamera>,
+    >,
+    mut images: ResMut<Assets<Image>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_screenshot_camera").entered();
+    if screenshot_requests.iter().count() == 0 {
+        return Ok(());
+    }
+    let window = primary_windows
+        .get_single()
+        .context("Failed to get primary window")?;
+    let (transform, projection, color_grading, tonemapping) = camera_query
+        .iter()
+        .next()
+        .context("Failed to find ingame camera for screenshot")?;
+
+    let size = Extent3d {
+        width: window.physical_width(),
+        height: window.physical_height(),
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("screenshot_image"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                ..default()
+            },
+            transform: transform.compute_transform(),
+            projection: projection.clone(),
+            color_grading: *color_grading,
+            tonemapping: *tonemapping,
+            ..default()
+        },
+        ScreenshotCamera {
+            image,
+            path: get_screenshot_path(),
+            frames_left: SCREENSHOT_WARMUP_FRAMES,
+        },
+        UiCameraConfig { show_ui: false },
+        Name::new("Screenshot Camera"),
+    ));
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn save_screenshots(captured_screenshots: Res<CapturedScreenshots>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_screenshots").entered();
+    let receiver = captured_screenshots
+        .0
+        .lock()
+        .map_err(|e| anyhow::Error::msg(format!("Failed to lock screenshot receiver: {e}")))?;
+    for screenshot in receiver.try_iter() {
+        let dir = screenshot
+            .path
+            .parent()
+            .context("Failed to get screenshot directory")?;
+        fs::create_dir_all(dir).context("Failed to create screenshot directory")?;
+        image::save_buffer(
+            &screenshot.path,
+            &screenshot.data,
+            screenshot.width,
+            screenshot.height,
+            image::ColorType::Rgba8,
+        )
+        .context("Failed to write screenshot")?;
+        info!(
+            "Successfully saved screenshot at {}",
+            screenshot.path.to_string_lossy()
+        );
+    }
+    Ok(())
+}
+
+fn extract_screenshot_cameras(mut commands: Commands, cameras: Extract<Query<&ScreenshotCamera>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("extract_screenshot_cameras").entered();
+    let pending = cameras
+        .iter()
+        .filter(|camera| camera.frames_left == 0)
+        .map(|camera| (camera.image.clone(), camera.path.clone()))
+        .collect();
+    commands.insert_resource(PendingScreenshots(pending));
+}
+
+/// Runs in the render world after the screenshot cameras have rendered their images
+#[sysfail(log(level = "error"))]
+fn read_back_screenshots(
+    pending_screenshots: Res<PendingScreenshots>,
+    gpu_images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    sender: Res<ScreenshotSender>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_back_screenshots").entered();
+    for (image, path) in pending_screenshots.0.iter() {
+        let gpu_image = gpu_images
+            .get(image)
+            .context("Failed to get screenshot image on the GPU")?;
+        let width = gpu_image.size.x as u32;
+        let height = gpu_image.size.y as u32;
+        let unpadded_bytes_per_row = width as usize * BYTES_PER_PIXEL;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT)
+            * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot_buffer"),
+            size: (padded_bytes_per_row * height as usize) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("screenshot_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row as u32),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        render_queue.submit([encoder.finish()]);
+
+        // Taking a screenshot is rare enough that stalling the renderer for it is fine
+        let slice = buffer.slice(..);
+        render_device.map_buffer(&slice, MapMode::Read, |_| {});
+        render_device.wgpu_device().poll(Maintain::Wait);
+        let data = slice
+            .get_mapped_range()
+            .chunks(padded_bytes_per_row)
+            .flat_map(|row| &row[..unpadded_bytes_per_row])
+            .copied()
+            .collect();
+        buffer.unmap();
+
+        sender
+            .0
+            .lock()
+            .map_err(|e| anyhow::Error::msg(format!("Failed to lock screenshot sender: {e}")))?
+            .send(Screenshot {
+                path: path.clone(),
+                width,
+                height,
+                data,
+            })
+            .context("Failed to send screenshot to the main world")?;
+    }
+    Ok(())
+}
+
+fn get_screenshot_path() -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f");
+    // Not using `with_extension`, since the milliseconds would be mistaken for an extension
+    Path::new("screenshots").join(format!("{timestamp}.png"))
+}
@@ -0,0 +1,110 @@
+use crate::combat::hazards::Landed;
+use crate::combat::health::Health;
+use crate::file_system_interaction::config::GameConfig;
+use crate::player_control::camera::IngameCamera;
+use crate::player_control::player_embodiment::Player;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Landing speed in m/s up to which the camera does not shake
+const MIN_LANDING_SPEED: f32 = 6.0;
+/// Trauma per m/s of landing speed above [`MIN_LANDING_SPEED`]
+const TRAUMA_PER_LANDING_SPEED: f32 = 0.08;
+
+/// Shared camera shake. Any system can push to it via [`CameraShake::add_trauma`], e.g. for explosions, hard landings, taking damage or firing.
+/// The trauma decays over time and the camera shakes by its square, so that small hits are subtle and big ones add up quickly.
+/// Can be turned off with `camera.shake.enabled` in the [`GameConfig`].
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct CameraShake {
+    /// From 0 (calm) to 1 (maximum shake)
+    pub(crate) trauma: f32,
+}
+
+impl CameraShake {
+    pub(crate) fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+/// Offsets the camera by smooth noise after the rig has placed it, so that the shake never accumulates in the rig itself.
+pub(crate) fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<IngameCamera>>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("shake_camera").entered();
+    let config = &config.camera.shake;
+    shake.trauma = (shake.trauma - config.trauma_decay * time.delta_seconds()).max(0.0);
+    if !config.enabled || shake.trauma <= 0.0 {
+        return;
+    }
+    let intensity = shake.trauma * shake.trauma;
+    let t = time.elapsed_seconds() * config.frequency;
+    let yaw = config.max_yaw_degrees.to_radians() * intensity * noise(0, t);
+    let pitch = config.max_pitch_degrees.to_radians() * intensity * noise(1, t);
+    let roll = config.max_roll_degrees.to_radians() * intensity * noise(2, t);
+    let offset = Vec3::new(noise(3, t), noise(4, t), noise(5, t)) * config.max_offset * intensity;
+    for mut transform in camera_query.iter_mut() {
+        let rotation = transform.rotation;
+        transform.translation += rotation * offset;
+        transform.rotation = rotation * Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+    }
+}
+
+pub(crate) fn shake_on_landing(
+    mut shake: ResMut<CameraShake>,
+    mut landings: EventReader<Landed>,
+    player_query: Query<(), With<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("shake_on_landing").entered();
+    for landing in landings.iter() {
+        let excess_speed = landing.fall_speed - MIN_LANDING_SPEED;
+        if excess_speed > 0.0 && player_query.contains(landing.entity) {
+            shake.add_trauma(excess_speed * TRAUMA_PER_LANDING_SPEED);
+        }
+    }
+}
+
+pub(crate) fn shake_on_damage(
+    mut shake: ResMut<CameraShake>,
+    player_query: Query<&Health, With<Player>>,
+    mut last_hit_points: Local<Option<f32>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("shake_on_damage").entered();
+    let Some(health) = player_query.iter().next() else {
+        *last_hit_points = None;
+        return;
+    };
+    if let Some(last_hit_points) = *last_hit_points && health.max_hit_points > 0.0 {
+        let damage = last_hit_points - health.hit_points;
+        if damage > 0.0 {
+            shake.add_trauma(damage / health.max_hit_points);
+        }
+    }
+    *last_hit_points = Some(health.hit_points);
+}
+
+/// Smooth value noise in [-1, 1]. Different seeds give independent curves.
+fn noise(seed: u32, t: f32) -> f32 {
+    let floor = t.floor();
+    let fraction = t - floor;
+    let smoothed = fraction * fraction * (3.0 - 2.0 * fraction);
+    let start = hash(seed, floor as i32);
+    let end = hash(seed, floor as i32 + 1);
+    start + (end - start) * smoothed
+}
+
+fn hash(seed: u32, index: i32) -> f32 {
+    let mut value = (index as u32)
+        .wrapping_mul(0x9E37_79B1)
+        .wrapping_add(seed.wrapping_mul(0x85EB_CA6B));
+    value ^= value >> 15;
+    value = value.wrapping_mul(0x2C1B_3C6D);
+    value ^= value >> 12;
+    (value as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
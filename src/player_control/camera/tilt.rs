@@ -0,0 +1,37 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::parkour::WallRunning;
+use crate::player_control::camera::IngameCamera;
+use crate::player_control::player_embodiment::Player;
+use crate::util::smoothness_to_lerp_factor;
+use bevy::prelude::*;
+
+/// Rolls the camera away from the wall the player is running on. Runs after the rig has placed the camera,
+/// since the rig itself knows nothing about roll.
+pub(crate) fn tilt_camera(
+    time: Res<Time>,
+    player_query: Query<&WallRunning, With<Player>>,
+    mut camera_query: Query<(&mut IngameCamera, &mut Transform), Without<Player>>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("tilt_camera").entered();
+    let wall_normal = player_query
+        .iter()
+        .next()
+        .and_then(|wall_running| wall_running.wall_normal);
+    for (mut camera, mut transform) in camera_query.iter_mut() {
+        let target_tilt = wall_normal.map_or(0.0, |normal| {
+            normal.dot(transform.right()) * config.camera.wall_run_tilt_degrees
+        });
+        let factor = smoothness_to_lerp_factor(
+            config.camera.wall_run_tilt_smoothing,
+            time.delta_seconds(),
+        );
+        camera.tilt_degrees += (target_tilt - camera.tilt_degrees) * factor;
+        let forward = transform.forward();
+        transform.rotate(Quat::from_axis_angle(
+            forward,
+            camera.tilt_degrees.to_radians(),
+        ));
+    }
+}
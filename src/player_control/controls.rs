@@ -0,0 +1,563 @@
+use crate::player_control::actions::{
+    create_camera_action_input_manager_bundle, create_player_action_input_manager_bundle,
+    create_ui_action_input_manager_bundle, CameraAction, PlayerAction, UiAction,
+};
+use anyhow::{Context, Result};
+use bevy::input::keyboard::{KeyboardInput, ScanCode};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use indexmap::IndexMap;
+use leafwing_input_manager::axislike::AxisType;
+use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::InputKind;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+
+const CONTROLS_PATH: &str = "./settings/controls.ron";
+
+/// Lets the player rebind their controls in a screen opened from the pause menu, see [`ControlsScreen`].
+/// The bindings are stored in `settings/controls.ron` and loaded at startup.
+/// Actions missing from that file keep the defaults from [`create_player_action_input_manager_bundle`] and friends.
+/// Keys are bound by their location on the keyboard, like the defaults, so that bindings work the same on every layout.
+/// Axes cannot be captured from a single press, so those actions cycle through [`get_axis_presets`] instead.
+pub(crate) fn controls_plugin(app: &mut App) {
+    app.init_resource::<Controls>()
+        .init_resource::<KeyNames>()
+        .add_system(load_controls.on_startup())
+        .add_system(learn_key_names)
+        .add_systems(
+            (show_controls_screen, capture_binding).run_if(resource_exists::<ControlsScreen>()),
+        )
+        .add_system(apply_controls);
+}
+
+/// The input maps that are applied to the player and the camera
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub(crate) struct Controls {
+    pub(crate) player: InputMap<PlayerAction>,
+    pub(crate) camera: InputMap<CameraAction>,
+    pub(crate) ui: InputMap<UiAction>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            player: create_player_action_input_manager_bundle().input_map,
+            camera: create_camera_action_input_manager_bundle().input_map,
+            ui: create_ui_action_input_manager_bundle().input_map,
+        }
+    }
+}
+
+/// The bindings as they are stored on disk. Actions are referred to by name, so that adding or reordering actions does not break the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct ControlsModel {
+    #[serde(default)]
+    player: IndexMap<String, Vec<UserInput>>,
+    #[serde(default)]
+    camera: IndexMap<String, Vec<UserInput>>,
+    #[serde(default)]
+    ui: IndexMap<String, Vec<UserInput>>,
+}
+
+/// Present while the controls screen is open
+#[derive(Debug, Clone, Resource, Default)]
+pub(crate) struct ControlsScreen {
+    /// The action and device for which we are waiting for the next input
+    capturing: Option<(Binding, Device)>,
+    /// Feedback about the last change, e.g. a conflict with another action
+    message: Option<String>,
+}
+
+/// A scan code does not know which key is printed at its location, since that depends on the keyboard layout.
+/// So the names of keys are learned as they are pressed and used to describe bindings.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+struct KeyNames(HashMap<ScanCode, KeyCode>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Binding {
+    Player(PlayerAction),
+    Camera(CameraAction),
+    Ui(UiAction),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Device {
+    KeyboardAndMouse,
+    Gamepad,
+}
+
+impl Device {
+    fn of(input: &UserInput) -> Self {
+        match input {
+            UserInput::Single(kind) => Self::of_kind(kind),
+            UserInput::VirtualDPad(dpad) => Self::of_kind(&dpad.up),
+            UserInput::VirtualAxis(axis) => Self::of_kind(&axis.negative),
+            UserInput::Chord(kinds) => kinds
+                .iter()
+                .next()
+                .map_or(Device::KeyboardAndMouse, Self::of_kind),
+        }
+    }
+
+    /// Axes count as part of the mouse if they are driven by its motion or wheel
+    fn of_kind(kind: &InputKind) -> Self {
+        match kind {
+            InputKind::GamepadButton(_) => Device::Gamepad,
+            InputKind::DualAxis(DualAxis { x, .. }) | InputKind::SingleAxis(x) => {
+                if matches!(x.axis_type, AxisType::Gamepad(_)) {
+                    Device::Gamepad
+                } else {
+                    Device::KeyboardAndMouse
+                }
+            }
+            _ => Device::KeyboardAndMouse,
+        }
+    }
+}
+
+/// Actions that are driven by an axis rather than a button
+fn is_axis_action(binding: &Binding) -> bool {
+    matches!(
+        binding,
+        Binding::Player(PlayerAction::Move | PlayerAction::AimDirection) | Binding::Camera(_)
+    )
+}
+
+/// The axes an axis action can be bound to on the device. An empty list means the binding cannot be changed,
+/// e.g. aiming in top-down mode always follows the cursor.
+fn get_axis_presets(binding: &Binding, device: Device) -> Vec<UserInput> {
+    match (binding, device) {
+        (Binding::Player(PlayerAction::Move), Device::KeyboardAndMouse) => {
+            vec![VirtualDPad::wasd().into(), VirtualDPad::arrow_keys().into()]
+        }
+        (Binding::Camera(CameraAction::Orbit), Device::KeyboardAndMouse) => {
+            vec![
+                DualAxis::mouse_motion().into(),
+                VirtualDPad::arrow_keys().into(),
+            ]
+        }
+        (
+            Binding::Player(PlayerAction::Move | PlayerAction::AimDirection)
+            | Binding::Camera(CameraAction::Orbit),
+            Device::Gamepad,
+        ) => vec![
+            DualAxis::left_stick().into(),
+            DualAxis::right_stick().into(),
+        ],
+        (Binding::Camera(CameraAction::Zoom), Device::KeyboardAndMouse) => {
+            vec![SingleAxis::mouse_wheel_y().into()]
+        }
+        _ => vec![],
+    }
+}
+
+fn get_axis_preset_name(input: &UserInput) -> Option<&'static str> {
+    [
+        (UserInput::from(VirtualDPad::wasd()), "WASD"),
+        (VirtualDPad::arrow_keys().into(), "Arrow keys"),
+        (DualAxis::mouse_motion().into(), "Mouse"),
+        (SingleAxis::mouse_wheel_y().into(), "Mouse wheel"),
+        (DualAxis::left_stick().into(), "Left stick"),
+        (DualAxis::right_stick().into(), "Right stick"),
+    ]
+    .into_iter()
+    .find_map(|(preset, name)| (preset == *input).then_some(name))
+}
+
+fn learn_key_names(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    mut key_names: ResMut<KeyNames>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("learn_key_names").entered();
+    for input in keyboard_input.iter() {
+        if let Some(key_code) = input.key_code {
+            key_names.0.insert(ScanCode(input.scan_code), key_code);
+        }
+    }
+}
+
+fn load_controls(mut controls: ResMut<Controls>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_controls").entered();
+    let path = Path::new(CONTROLS_PATH);
+    if !path.exists() {
+        return;
+    }
+    let model = fs::read_to_string(path)
+        .context("Failed to read controls file")
+        .and_then(|serialized| {
+            ron::from_str::<ControlsModel>(&serialized).context("Failed to parse controls file")
+        });
+    match model {
+        Ok(model) => {
+            merge_bindings(&mut controls.player, &model.player);
+            merge_bindings(&mut controls.camera, &model.camera);
+            merge_bindings(&mut controls.ui, &model.ui);
+        }
+        Err(e) => error!("Using the default controls: {e:?}"),
+    }
+}
+
+fn save_controls(controls: &Controls) -> Result<()> {
+    let model = ControlsModel {
+        player: get_bindings(&controls.player),
+        camera: get_bindings(&controls.camera),
+        ui: get_bindings(&controls.ui),
+    };
+    let serialized = ron::ser::to_string_pretty(&model, default())?;
+    let path = Path::new(CONTROLS_PATH);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create settings directory")?;
+    }
+    fs::write(path, serialized).context("Failed to write controls file")?;
+    Ok(())
+}
+
+fn get_bindings<A: Actionlike + Debug>(
+    input_map: &InputMap<A>,
+) -> IndexMap<String, Vec<UserInput>> {
+    A::variants()
+        .map(|action| {
+            let inputs = input_map.get(action.clone()).iter().cloned().collect();
+            (format!("{action:?}"), inputs)
+        })
+        .collect()
+}
+
+fn merge_bindings<A: Actionlike + Debug>(
+    input_map: &mut InputMap<A>,
+    bindings: &IndexMap<String, Vec<UserInput>>,
+) {
+    for action in A::variants() {
+        let Some(inputs) = bindings.get(&format!("{action:?}")) else {
+            continue;
+        };
+        input_map.clear_action(action.clone());
+        for input in inputs {
+            input_map.insert(input.clone(), action.clone());
+        }
+    }
+}
+
+fn apply_controls(
+    controls: Res<Controls>,
+    mut player_maps: Query<&mut InputMap<PlayerAction>>,
+    mut camera_maps: Query<&mut InputMap<CameraAction>>,
+    mut ui_maps: Query<&mut InputMap<UiAction>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_controls").entered();
+    // Newly spawned players and cameras come with the default bindings
+    for mut input_map in player_maps.iter_mut() {
+        if controls.is_changed() || input_map.is_added() {
+            *input_map = controls.player.clone();
+        }
+    }
+    for mut input_map in camera_maps.iter_mut() {
+        if controls.is_changed() || input_map.is_added() {
+            *input_map = controls.camera.clone();
+        }
+    }
+    for mut input_map in ui_maps.iter_mut() {
+        if controls.is_changed() || input_map.is_added() {
+            *input_map = controls.ui.clone();
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn show_controls_screen(
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    mut screen: ResMut<ControlsScreen>,
+    mut controls: ResMut<Controls>,
+    key_names: Res<KeyNames>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_controls_screen").entered();
+    let mut close = false;
+    let mut reset = false;
+    let mut clicked = None;
+    egui::Window::new("Controls")
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if let Some(message) = &screen.message {
+                ui.colored_label(egui::Color32::YELLOW, message);
+            }
+            egui::ScrollArea::vertical()
+                .max_height(500.)
+                .show(ui, |ui| {
+                    egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                        ui.strong("Action");
+                        ui.strong("Keyboard & mouse");
+                        ui.strong("Gamepad");
+                        ui.end_row();
+                        let rows = PlayerAction::variants()
+                            .map(|action| {
+                                let inputs = controls.player.get(action).iter().cloned().collect();
+                                (Binding::Player(action), inputs)
+                            })
+                            .chain(CameraAction::variants().map(|action| {
+                                let inputs = controls
+                                    .camera
+                                    .get(action.clone())
+                                    .iter()
+                                    .cloned()
+                                    .collect();
+                                (Binding::Camera(action), inputs)
+                            }))
+                            .chain(UiAction::variants().map(|action| {
+                                let inputs =
+                                    controls.ui.get(action.clone()).iter().cloned().collect();
+                                (Binding::Ui(action), inputs)
+                            }));
+                        for (binding, inputs) in rows {
+                            if let Some(device) =
+                                show_binding_row(ui, &screen, &binding, &inputs, &key_names)
+                            {
+                                clicked = Some((binding, device));
+                            }
+                        }
+                    });
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                reset = ui.button("Reset to defaults").clicked();
+                close = ui.button("Back").clicked();
+            });
+        });
+    if let Some((binding, device)) = clicked {
+        if is_axis_action(&binding) {
+            let presets = get_axis_presets(&binding, device);
+            let input = match binding.clone() {
+                Binding::Player(action) => {
+                    cycle_axis_preset(&mut controls.player, action, device, &presets)
+                }
+                Binding::Camera(action) => {
+                    cycle_axis_preset(&mut controls.camera, action, device, &presets)
+                }
+                Binding::Ui(action) => {
+                    cycle_axis_preset(&mut controls.ui, action, device, &presets)
+                }
+            };
+            if let Some(input) = input {
+                screen.message = get_conflict_message(&controls, &binding, &input, &key_names);
+                save_controls(&controls).context("Failed to save controls")?;
+            }
+        } else {
+            screen.capturing = Some((binding, device));
+            screen.message = None;
+        }
+    }
+    if reset {
+        *controls = default();
+        screen.message = Some("Restored the default controls".to_string());
+        save_controls(&controls)?;
+    }
+    if close {
+        commands.remove_resource::<ControlsScreen>();
+    }
+    Ok(())
+}
+
+/// Returns the device whose binding was clicked
+fn show_binding_row(
+    ui: &mut egui::Ui,
+    screen: &ControlsScreen,
+    binding: &Binding,
+    inputs: &[UserInput],
+    key_names: &KeyNames,
+) -> Option<Device> {
+    let name = match binding {
+        Binding::Player(action) => format!("{action:?}"),
+        Binding::Camera(action) => format!("{action:?}"),
+        Binding::Ui(action) => format!("{action:?}"),
+    };
+    ui.label(name);
+    let mut clicked = None;
+    for device in [Device::KeyboardAndMouse, Device::Gamepad] {
+        let text = inputs
+            .iter()
+            .filter(|input| Device::of(input) == device)
+            .map(|input| describe_input(input, key_names))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let is_capturing = screen.capturing == Some((binding.clone(), device));
+        let is_rebindable =
+            !is_axis_action(binding) || !get_axis_presets(binding, device).is_empty();
+        if is_capturing {
+            ui.label("Press any button...");
+        } else if is_rebindable {
+            let text = if text.is_empty() {
+                "-".to_string()
+            } else {
+                text
+            };
+            if ui.button(text).clicked() {
+                clicked = Some(device);
+            }
+        } else {
+            ui.label(text);
+        }
+    }
+    ui.end_row();
+    clicked
+}
+
+fn describe_input(input: &UserInput, key_names: &KeyNames) -> String {
+    if let Some(name) = get_axis_preset_name(input) {
+        return name.to_string();
+    }
+    match input {
+        UserInput::Single(kind) => describe_input_kind(kind, key_names),
+        UserInput::Chord(kinds) => kinds
+            .iter()
+            .map(|kind| describe_input_kind(kind, key_names))
+            .collect::<Vec<_>>()
+            .join(" + "),
+        other => format!("{other:?}"),
+    }
+}
+
+fn describe_input_kind(kind: &InputKind, key_names: &KeyNames) -> String {
+    match kind {
+        InputKind::Keyboard(key) => format!("{key:?}"),
+        InputKind::KeyLocation(scan_code) => key_names
+            .0
+            .get(scan_code)
+            .map_or_else(|| format!("Key #{}", scan_code.0), |key| format!("{key:?}")),
+        InputKind::Mouse(button) => format!("Mouse {button:?}"),
+        InputKind::GamepadButton(button) => format!("{button:?}"),
+        other => format!("{other:?}"),
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn capture_binding(
+    mut commands: Commands,
+    mut screen: ResMut<ControlsScreen>,
+    mut controls: ResMut<Controls>,
+    key_names: Res<KeyNames>,
+    keys: Res<Input<KeyCode>>,
+    scan_codes: Res<Input<ScanCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("capture_binding").entered();
+    let Some((binding, device)) = screen.capturing.clone() else {
+        if keys.just_pressed(KeyCode::Escape) {
+            commands.remove_resource::<ControlsScreen>();
+        }
+        return Ok(());
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        screen.capturing = None;
+        return Ok(());
+    }
+    let input: Option<UserInput> = match device {
+        // Bound by location like the defaults, so that conflicts with them are found
+        Device::KeyboardAndMouse => scan_codes
+            .get_just_pressed()
+            .next()
+            .map(|scan_code| InputKind::KeyLocation(*scan_code).into())
+            .or_else(|| {
+                mouse_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| InputKind::Mouse(*button).into())
+            }),
+        Device::Gamepad => gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| InputKind::GamepadButton(button.button_type).into()),
+    };
+    let Some(input) = input else {
+        return Ok(());
+    };
+    screen.capturing = None;
+    match binding.clone() {
+        Binding::Player(action) => rebind(&mut controls.player, action, device, input.clone()),
+        Binding::Camera(action) => rebind(&mut controls.camera, action, device, input.clone()),
+        Binding::Ui(action) => rebind(&mut controls.ui, action, device, input.clone()),
+    };
+    screen.message = get_conflict_message(&controls, &binding, &input, &key_names);
+    save_controls(&controls).context("Failed to save controls")?;
+    Ok(())
+}
+
+fn get_conflict_message(
+    controls: &Controls,
+    binding: &Binding,
+    input: &UserInput,
+    key_names: &KeyNames,
+) -> Option<String> {
+    // The player, camera and UI actions are all active at the same time, so they can conflict with each other as well
+    let conflicts: Vec<_> = find_conflicts(&controls.player, input, Binding::Player)
+        .chain(find_conflicts(&controls.camera, input, Binding::Camera))
+        .chain(find_conflicts(&controls.ui, input, Binding::Ui))
+        .filter(|(other, _)| other != binding)
+        .map(|(_, name)| name)
+        .collect();
+    (!conflicts.is_empty()).then(|| {
+        format!(
+            "{} is also bound to {}",
+            describe_input(input, key_names),
+            conflicts.join(", ")
+        )
+    })
+}
+
+/// Binds the action to the preset that follows its current binding on the device and returns it
+fn cycle_axis_preset<A: Actionlike>(
+    input_map: &mut InputMap<A>,
+    action: A,
+    device: Device,
+    presets: &[UserInput],
+) -> Option<UserInput> {
+    let current = input_map
+        .get(action.clone())
+        .iter()
+        .find(|input| Device::of(input) == device)
+        .cloned();
+    let next_index = current
+        .and_then(|current| presets.iter().position(|preset| *preset == current))
+        .map_or(0, |index| (index + 1) % presets.len());
+    let input = presets.get(next_index)?.clone();
+    rebind(input_map, action, device, input.clone());
+    Some(input)
+}
+
+/// Replaces the bindings of the action on the given device
+fn rebind<A: Actionlike>(input_map: &mut InputMap<A>, action: A, device: Device, input: UserInput) {
+    let kept: Vec<_> = input_map
+        .get(action.clone())
+        .iter()
+        .filter(|existing| Device::of(existing) != device)
+        .cloned()
+        .collect();
+    input_map.clear_action(action.clone());
+    for existing in kept {
+        input_map.insert(existing, action.clone());
+    }
+    input_map.insert(input, action);
+}
+
+/// Returns all actions of the input map that are triggered by the input, together with their names
+fn find_conflicts<'a, A: Actionlike + Debug>(
+    input_map: &'a InputMap<A>,
+    input: &'a UserInput,
+    to_binding: impl Fn(A) -> Binding + 'a,
+) -> impl Iterator<Item = (Binding, String)> + 'a {
+    A::variants()
+        .filter(|action| input_map.get(action.clone()).contains(input))
+        .map(move |action| (to_binding(action.clone()), format!("{action:?}")))
+}
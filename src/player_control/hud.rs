@@ -0,0 +1,50 @@
+use crate::movement::general_movement::Dashing;
+use crate::movement::water::Breath;
+use crate::player_control::camera::is_hud_visible;
+use crate::player_control::player_embodiment::Player;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+/// Draws the in-game heads-up display, which currently consists of the dash cooldown indicator and the breath meter.
+pub(crate) fn hud_plugin(app: &mut App) {
+    app.add_systems(
+        (
+            show_dash_cooldown.run_if(is_hud_visible),
+            show_breath.run_if(is_hud_visible),
+        )
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+fn show_dash_cooldown(mut egui_contexts: EguiContexts, players: Query<&Dashing, With<Player>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_dash_cooldown").entered();
+    for dashing in &players {
+        egui::Area::new("dash_cooldown")
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0., -30.))
+            .interactable(false)
+            .show(egui_contexts.ctx_mut(), |ui| {
+                ui.set_width(120.);
+                ui.add(egui::ProgressBar::new(dashing.get_readiness()).text("Dash"));
+            });
+    }
+}
+
+fn show_breath(mut egui_contexts: EguiContexts, players: Query<&Breath, With<Player>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_breath").entered();
+    for breath in &players {
+        // Only bother the player while they are actually holding their breath
+        if breath.remaining_seconds >= breath.max_seconds {
+            continue;
+        }
+        egui::Area::new("breath")
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0., -60.))
+            .interactable(false)
+            .show(egui_contexts.ctx_mut(), |ui| {
+                ui.set_width(120.);
+                ui.add(egui::ProgressBar::new(breath.get_fraction()).text("Breath"));
+            });
+    }
+}
@@ -0,0 +1,207 @@
+use crate::level_instantiation::spawning::objects::enemy::EnemyTag;
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::camera::{CameraUpdateSystemSet, IngameCamera, IngameCameraKind};
+use crate::player_control::player_embodiment::Player;
+use crate::simulation::InterpolationSystemSet;
+use crate::util::trait_extension::F32Ext;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// How far the stick needs to be pushed sideways to switch to the next target
+const CYCLE_THRESHOLD: f32 = 0.7;
+/// How far the stick needs to return before another switch is possible
+const CYCLE_RESET_THRESHOLD: f32 = 0.3;
+
+/// Lets the player lock the camera onto an enemy with [`PlayerAction::LockOn`].
+/// The camera keeps the target framed via [`IngameCamera::secondary_target`], the same way it frames the speaker during a dialog.
+/// Flicking the right stick sideways switches to the next enemy in that direction.
+/// The lock breaks when the target dies, gets too far away or stays hidden behind something for too long.
+pub(crate) fn lock_on_plugin(app: &mut App) {
+    app.register_type::<LockOn>().add_system(
+        update_lock_on
+            .after(InterpolationSystemSet)
+            .before(CameraUpdateSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct LockOn {
+    /// The enemy the camera is locked onto, if any
+    pub(crate) target: Option<Entity>,
+    /// Distance in m from the camera up to which enemies can be locked onto
+    pub(crate) max_distance: f32,
+    /// Maximum angle in degrees between the camera's forward direction and an enemy that can be locked onto
+    pub(crate) max_angle_degrees: f32,
+    /// Seconds the target may be hidden before the lock breaks
+    pub(crate) max_hidden_seconds: f32,
+    pub(crate) hidden_seconds: f32,
+    /// Whether the stick has returned to the center since the last switch
+    pub(crate) can_cycle: bool,
+}
+
+impl Default for LockOn {
+    fn default() -> Self {
+        Self {
+            target: None,
+            max_distance: 20.0,
+            max_angle_degrees: 60.0,
+            max_hidden_seconds: 0.5,
+            hidden_seconds: 0.0,
+            can_cycle: true,
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn update_lock_on(
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &ActionState<PlayerAction>, &mut LockOn), With<Player>>,
+    camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
+    enemies: Query<(Entity, &Transform), (With<EnemyTag>, Without<Player>)>,
+    rapier_context: Res<RapierContext>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_lock_on").entered();
+    let Some((camera, camera_transform)) = camera_query.iter().next() else {
+        return Ok(());
+    };
+    for (player, actions, mut lock_on) in &mut player_query {
+        if camera.kind == IngameCameraKind::FixedAngle {
+            lock_on.target = None;
+            continue;
+        }
+        let filter = QueryFilter::new()
+            .exclude_collider(player)
+            .exclude_sensors();
+        let candidates: Vec<_> = enemies
+            .iter()
+            .filter(|(enemy, enemy_transform)| {
+                is_in_range(camera_transform, enemy_transform.translation, &lock_on)
+                    && is_visible(
+                        camera_transform,
+                        *enemy,
+                        enemy_transform.translation,
+                        &rapier_context,
+                        filter,
+                    )
+            })
+            .map(|(enemy, enemy_transform)| (enemy, enemy_transform.translation))
+            .collect();
+
+        if actions.just_pressed(PlayerAction::LockOn) {
+            lock_on.target = if lock_on.target.is_some() {
+                None
+            } else {
+                candidates
+                    .iter()
+                    .min_by(|(_, a), (_, b)| {
+                        let distance_a = a.distance_squared(camera_transform.translation);
+                        let distance_b = b.distance_squared(camera_transform.translation);
+                        distance_a.total_cmp(&distance_b)
+                    })
+                    .map(|(enemy, _)| *enemy)
+            };
+            lock_on.hidden_seconds = 0.0;
+        }
+
+        let Some(target) = lock_on.target else {
+            continue;
+        };
+        // Dead enemies are despawned
+        let Ok((_, target_transform)) = enemies.get(target) else {
+            lock_on.target = None;
+            continue;
+        };
+        let target_translation = target_transform.translation;
+        let is_too_far = target_translation.distance_squared(camera_transform.translation)
+            > lock_on.max_distance.squared();
+        if is_too_far {
+            lock_on.target = None;
+            continue;
+        }
+        if is_visible(
+            camera_transform,
+            target,
+            target_translation,
+            &rapier_context,
+            filter,
+        ) {
+            lock_on.hidden_seconds = 0.0;
+        } else {
+            lock_on.hidden_seconds += time.delta_seconds();
+            if lock_on.hidden_seconds > lock_on.max_hidden_seconds {
+                lock_on.target = None;
+            }
+            continue;
+        }
+
+        let stick = actions
+            .axis_pair(PlayerAction::AimDirection)
+            .context("Player aim direction is not an axis pair")?
+            .x();
+        if stick.abs() < CYCLE_RESET_THRESHOLD {
+            lock_on.can_cycle = true;
+        } else if lock_on.can_cycle && stick.abs() > CYCLE_THRESHOLD {
+            lock_on.can_cycle = false;
+            if let Some(next) =
+                get_next_target(camera_transform, target_translation, &candidates, stick > 0.0)
+            {
+                lock_on.target = Some(next);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_in_range(camera_transform: &Transform, position: Vec3, lock_on: &LockOn) -> bool {
+    let to_position = position - camera_transform.translation;
+    to_position.length_squared() <= lock_on.max_distance.squared()
+        && to_position.angle_between(camera_transform.forward())
+            <= lock_on.max_angle_degrees.to_radians()
+}
+
+fn is_visible(
+    camera_transform: &Transform,
+    entity: Entity,
+    position: Vec3,
+    rapier_context: &RapierContext,
+    filter: QueryFilter,
+) -> bool {
+    let to_position = position - camera_transform.translation;
+    let distance = to_position.length();
+    let Some(direction) = to_position.try_normalize() else {
+        return true;
+    };
+    rapier_context
+        .cast_ray(camera_transform.translation, direction, distance, true, filter)
+        .map_or(true, |(hit, _toi)| hit == entity)
+}
+
+/// Returns the candidate that is closest to the current target in the given direction, as seen from the camera.
+fn get_next_target(
+    camera_transform: &Transform,
+    current: Vec3,
+    candidates: &[(Entity, Vec3)],
+    to_the_right: bool,
+) -> Option<Entity> {
+    let get_angle = |position: Vec3| {
+        let direction = position - camera_transform.translation;
+        direction
+            .dot(camera_transform.right())
+            .atan2(direction.dot(camera_transform.forward()))
+    };
+    let current_angle = get_angle(current);
+    candidates
+        .iter()
+        .map(|(enemy, position)| (*enemy, get_angle(*position) - current_angle))
+        .filter(|(_, angle)| if to_the_right { *angle > 0.0 } else { *angle < 0.0 })
+        .min_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+        .map(|(enemy, _)| enemy)
+}
@@ -1,9 +1,12 @@
 use crate::combat::shoot::Shooting;
 use crate::file_system_interaction::config::GameConfig;
-use crate::movement::general_movement::{GeneralMovementSystemSet, Grounded, Jumping, Walking};
+use crate::movement::general_movement::{Climbing, Crouching, Dashing, Jumping, Swimming, Walking};
+use crate::movement::ladder::LadderClimber;
 use crate::player_control::actions::{DualAxisDataExt, PlayerAction};
-use crate::player_control::camera::{CameraUpdateSystemSet, IngameCamera, IngameCameraKind};
-use crate::spatial_audio::LoopAudioEmitter;
+use crate::player_control::camera::{
+    is_camera_detached, CameraUpdateSystemSet, IngameCamera, IngameCameraKind,
+};
+use crate::simulation::SimulationSet;
 use crate::util::smoothness_to_lerp_factor;
 use crate::util::trait_extension::{F32Ext, TransformExt, Vec3Ext};
 use crate::world_interaction::dialog::CurrentDialog;
@@ -21,19 +24,30 @@ use std::ops::DerefMut;
 pub(crate) fn player_embodiment_plugin(app: &mut App) {
     app.register_type::<Timer>()
         .register_type::<Player>()
+        .register_type::<LatchedActions>()
         .add_systems(
             (
                 handle_jump,
+                handle_crouch,
+                handle_aim,
                 handle_horizontal_movement,
-                handle_speed_effects,
+                handle_climbing,
+                handle_swimming,
+                handle_dash,
                 rotate_to_speaker.run_if(resource_exists::<CurrentDialog>()),
-                control_walking_sound,
                 handle_camera_kind,
                 handle_shoot,
+                clear_latched_actions,
             )
                 .chain()
+                .in_set(SimulationSet::Input)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(latch_actions.in_set(OnUpdate(GameState::Playing)))
+        .add_system(
+            handle_speed_effects
+                .run_if(not(is_camera_detached))
                 .after(CameraUpdateSystemSet)
-                .before(GeneralMovementSystemSet)
                 .in_set(OnUpdate(GameState::Playing)),
         );
 }
@@ -42,6 +56,37 @@ pub(crate) fn player_embodiment_plugin(app: &mut App) {
 #[reflect(Component, Serialize, Deserialize)]
 pub(crate) struct Player;
 
+/// Presses of actions that should only trigger once per press. [`ActionState::just_pressed`] is only true for a single frame,
+/// which the fixed timestep would miss on frames without a tick and see twice on frames with several ticks.
+/// So presses are latched here every frame and cleared after the next tick handled them.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct LatchedActions {
+    pub(crate) jump: bool,
+    pub(crate) dash: bool,
+}
+
+fn latch_actions(
+    mut player_query: Query<(&ActionState<PlayerAction>, &mut LatchedActions), With<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("latch_actions").entered();
+    for (actions, mut latched) in &mut player_query {
+        latched.jump |= actions.just_pressed(PlayerAction::Jump);
+        latched.dash |= actions.just_pressed(PlayerAction::Dash);
+    }
+}
+
+fn clear_latched_actions(mut player_query: Query<&mut LatchedActions, With<Player>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("clear_latched_actions").entered();
+    for mut latched in &mut player_query {
+        *latched = default();
+    }
+}
+
 fn handle_shoot(
     mut player_query: Query<(&ActionState<PlayerAction>, &mut Shooting), With<Player>>,
 ) {
@@ -60,9 +105,46 @@ fn handle_jump(mut player_query: Query<(&ActionState<PlayerAction>, &mut Jumping
     }
 }
 
+fn handle_crouch(
+    mut player_query: Query<
+        (&ActionState<PlayerAction>, &mut Crouching),
+        (With<Player>, Without<Swimming>),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_crouch").entered();
+    for (actions, mut crouching) in &mut player_query {
+        crouching.requested |= actions.pressed(PlayerAction::Crouch);
+    }
+}
+
+fn handle_aim(
+    mut player_query: Query<(&ActionState<PlayerAction>, &mut Shooting), With<Player>>,
+    camera_query: Query<&IngameCamera, Without<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_aim").entered();
+    let Some(camera) = camera_query.iter().next() else {
+        return;
+    };
+    for (actions, mut shooting) in &mut player_query {
+        // Top-down aiming does not look through the sights
+        shooting.aiming =
+            actions.pressed(PlayerAction::Aim) && camera.kind != IngameCameraKind::FixedAngle;
+    }
+}
+
 #[sysfail(log(level = "error"))]
 fn handle_horizontal_movement(
-    mut player_query: Query<(&ActionState<PlayerAction>, &mut Walking, &Transform), With<Player>>,
+    mut player_query: Query<
+        (
+            &ActionState<PlayerAction>,
+            &mut Walking,
+            &Transform,
+            Option<&Shooting>,
+        ),
+        With<Player>,
+    >,
     camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
@@ -71,42 +153,152 @@ fn handle_horizontal_movement(
         return Ok(());
     };
 
-    for (actions, mut walk, player_transform) in &mut player_query {
+    for (actions, mut walk, player_transform, shooting) in &mut player_query {
         if let Some(movement) = actions
             .axis_pair(PlayerAction::Move)
             .context("Player movement is not an axis pair")?
             .max_normalized()
         {
-            let up = player_transform.up();
-            let forward = if camera.kind == IngameCameraKind::FixedAngle {
-                camera_transform.up()
-            } else {
-                camera_transform.forward()
-            }
-            .split(up)
-            .horizontal
-            .normalize();
-
-            let sideways = forward.cross(up);
-            let forward_action = forward * movement.y;
-            let sideways_action = sideways * movement.x;
-
-            let is_looking_backward = forward.dot(forward_action) < 0.0;
+            let is_looking_backward = movement.y < 0.0;
             let is_first_person = camera.kind == IngameCameraKind::FirstPerson;
             let modifier = if is_looking_backward && is_first_person {
                 0.7
             } else {
                 1.
             };
-            let direction = forward_action * modifier + sideways_action;
+            let movement = Vec2::new(movement.x, movement.y * modifier);
+            let direction = get_camera_relative_direction(
+                movement,
+                camera,
+                camera_transform,
+                player_transform.up(),
+            );
+
+            let aim_movement_factor = shooting
+                .filter(|shooting| shooting.aiming)
+                .map(|shooting| shooting.aim_movement_factor);
+            walk.direction = Some(direction * aim_movement_factor.unwrap_or(1.0));
+            walk.sprinting = actions.pressed(PlayerAction::Sprint) && aim_movement_factor.is_none();
+        }
+    }
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn handle_climbing(
+    mut player_query: Query<
+        (
+            &ActionState<PlayerAction>,
+            &LatchedActions,
+            &mut Climbing,
+            &mut LadderClimber,
+            &Transform,
+        ),
+        With<Player>,
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_climbing").entered();
+    for (actions, latched, mut climbing, mut climber, transform) in &mut player_query {
+        let movement = actions
+            .axis_pair(PlayerAction::Move)
+            .context("Player movement is not an axis pair")?
+            .xy();
+        // On a ladder, "forward" means up
+        climbing.direction = Some(transform.up() * movement.y.clamp(-1.0, 1.0));
+        climber.detach_requested |= latched.jump;
+    }
+    Ok(())
+}
+
+fn handle_swimming(
+    mut player_query: Query<
+        (
+            &ActionState<PlayerAction>,
+            &mut Swimming,
+            &Walking,
+            &Transform,
+        ),
+        With<Player>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_swimming").entered();
+    for (actions, mut swimming, walking, transform) in &mut player_query {
+        // Jump to swim up, crouch to dive
+        let vertical = match (
+            actions.pressed(PlayerAction::Jump),
+            actions.pressed(PlayerAction::Crouch),
+        ) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        let direction = walking.direction.unwrap_or_default() + transform.up() * vertical;
+        swimming.direction = (!direction.is_approx_zero()).then(|| direction.clamp_length_max(1.0));
+    }
+}
 
-            walk.direction = Some(direction);
-            walk.sprinting = actions.pressed(PlayerAction::Sprint);
+#[sysfail(log(level = "error"))]
+fn handle_dash(
+    mut player_query: Query<
+        (
+            &ActionState<PlayerAction>,
+            &LatchedActions,
+            &mut Dashing,
+            &Transform,
+        ),
+        With<Player>,
+    >,
+    camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_dash").entered();
+    let Some((camera, camera_transform)) = camera_query.iter().next() else {
+        return Ok(());
+    };
+
+    for (actions, latched, mut dashing, player_transform) in &mut player_query {
+        if !latched.dash {
+            continue;
         }
+        // Without any movement input, dash straight ahead
+        let movement = actions
+            .axis_pair(PlayerAction::Move)
+            .context("Player movement is not an axis pair")?
+            .max_normalized()
+            .unwrap_or(Vec2::Y);
+        let direction = get_camera_relative_direction(
+            movement,
+            camera,
+            camera_transform,
+            player_transform.up(),
+        );
+        dashing.requested = Some(direction);
     }
     Ok(())
 }
 
+/// Translates movement input into a direction in the world, where "up" on the input means away from the camera.
+pub(crate) fn get_camera_relative_direction(
+    movement: Vec2,
+    camera: &IngameCamera,
+    camera_transform: &Transform,
+    up: Vec3,
+) -> Vec3 {
+    let forward = if camera.kind == IngameCameraKind::FixedAngle {
+        camera_transform.up()
+    } else {
+        camera_transform.forward()
+    }
+    .split(up)
+    .horizontal
+    .normalize();
+
+    let sideways = forward.cross(up);
+    forward * movement.y + sideways * movement.x
+}
+
 fn handle_camera_kind(
     mut with_player: Query<(&mut Transform, &mut Visibility), With<Player>>,
     camera_query: Query<(&Transform, &IngameCamera), Without<Player>>,
@@ -132,15 +324,17 @@ fn handle_camera_kind(
 }
 
 fn handle_speed_effects(
-    velocities: Query<&Velocity, With<Player>>,
-    mut projections: Query<&mut Projection, With<IngameCamera>>,
+    time: Res<Time>,
+    player_query: Query<(&Velocity, Option<&Shooting>), With<Player>>,
+    mut camera_query: Query<(&mut IngameCamera, &mut Projection)>,
     config: Res<GameConfig>,
 ) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("handle_speed_effects").entered();
-    for velocity in velocities.iter() {
+    for (velocity, shooting) in player_query.iter() {
         let speed_squared = velocity.linvel.length_squared();
-        for mut projection in projections.iter_mut() {
+        let is_aiming = shooting.map_or(false, |shooting| shooting.aiming);
+        for (mut camera, mut projection) in camera_query.iter_mut() {
             if let Projection::Perspective(ref mut perspective) = projection.deref_mut() {
                 let fov_saturation_speed = config.player.fov_saturation_speed;
                 let min_fov = config.player.min_fov;
@@ -148,14 +342,24 @@ fn handle_speed_effects(
                 let scale = (speed_squared / fov_saturation_speed.squared())
                     .min(1.0)
                     .squared();
-                perspective.fov = min_fov + (max_fov - min_fov) * scale;
+                let speed_fov = min_fov + (max_fov - min_fov) * scale;
+
+                let target_zoom = if is_aiming { 1.0 } else { 0.0 };
+                let factor = smoothness_to_lerp_factor(
+                    config.player.aim_zoom_smoothing,
+                    time.delta_seconds(),
+                );
+                camera.aim_zoom = camera.aim_zoom.lerp(target_zoom, factor);
+                let aim_fov = shooting.map_or(speed_fov, |shooting| shooting.aim_fov);
+                perspective.fov = speed_fov.lerp(aim_fov, camera.aim_zoom);
+                camera.zoom_ratio = perspective.fov / speed_fov;
             }
         }
     }
 }
 
 fn rotate_to_speaker(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut with_player: Query<(&mut Transform, &Velocity), With<Player>>,
     without_player: Query<&Transform, Without<Player>>,
     current_dialog: Res<CurrentDialog>,
@@ -166,7 +370,7 @@ fn rotate_to_speaker(
     let Ok(speaker_transform) = without_player.get(current_dialog.source) else {
          return;
     };
-    let dt = time.delta_seconds();
+    let dt = fixed_time.period.as_secs_f32();
 
     for (mut transform, velocity) in with_player.iter_mut() {
         let horizontal_velocity = velocity.linvel.split(transform.up()).horizontal;
@@ -183,29 +387,3 @@ fn rotate_to_speaker(
     }
 }
 
-#[sysfail(log(level = "error"))]
-fn control_walking_sound(
-    time: Res<Time>,
-    mut character_query: Query<(&Velocity, &Transform, &Grounded, &mut LoopAudioEmitter)>,
-) -> Result<()> {
-    #[cfg(feature = "tracing")]
-    let _span = info_span!("control_walking_sound").entered();
-    for (velocity, transform, grounded, mut emitter) in character_query.iter_mut() {
-        let has_horizontal_movement = !velocity
-            .linvel
-            .split(transform.up())
-            .horizontal
-            .is_approx_zero();
-        let is_moving_on_ground = has_horizontal_movement && grounded.0;
-        if is_moving_on_ground && !time.is_paused() {
-            if emitter.playback_state.is_some() {
-                emitter.resume(default());
-            } else {
-                emitter.play(default());
-            }
-        } else {
-            emitter.pause(default());
-        }
-    }
-    Ok(())
-}
@@ -0,0 +1,179 @@
+use crate::combat::shoot::Shooting;
+use crate::movement::general_movement::GeneralMovementSystemSet;
+use crate::player_control::actions::{DualAxisDataExt, PlayerAction};
+use crate::player_control::camera::{IngameCamera, IngameCameraKind};
+use crate::player_control::player_embodiment::{get_camera_relative_direction, Player};
+use crate::simulation::{InterpolationSystemSet, SimulationSet};
+use crate::util::trait_extension::{TransformExt, Vec3Ext};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Distance in m in front of the player at which the aim point is placed when aiming with a stick
+const STICK_AIM_DISTANCE: f32 = 5.0;
+/// Height in m above the ground at which the reticle floats so that it does not flicker with the floor
+const RETICLE_HEIGHT: f32 = 0.02;
+
+/// Handles twin-stick style aiming while the camera is in [`IngameCameraKind::FixedAngle`].
+/// The mouse cursor or right stick is projected onto the ground plane under the player, which becomes [`Shooting::target`].
+/// The player faces that point, projectiles travel horizontally towards it, and it is marked by an [`AimReticle`].
+pub(crate) fn top_down_aim_plugin(app: &mut App) {
+    app.register_type::<AimReticle>()
+        .add_system(
+            update_aim_target
+                .in_set(SimulationSet::Input)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(
+            face_aim_target
+                .after(GeneralMovementSystemSet)
+                .in_set(SimulationSet::Gameplay)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_systems(
+            (spawn_reticle, move_reticle)
+                .chain()
+                .after(InterpolationSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+/// Marks the spot on the ground the player is aiming at in top-down mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct AimReticle;
+
+#[sysfail(log(level = "error"))]
+fn update_aim_target(
+    mut player_query: Query<
+        (&ActionState<PlayerAction>, &mut Shooting, &Transform, &Collider),
+        With<Player>,
+    >,
+    camera_query: Query<
+        (&IngameCamera, &Camera, &Transform, &GlobalTransform),
+        Without<Player>,
+    >,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_aim_target").entered();
+    let Some((ingame_camera, camera, camera_transform, camera_global_transform)) =
+        camera_query.iter().next()
+    else {
+        return Ok(());
+    };
+    let cursor = windows.get_single().ok().and_then(Window::cursor_position);
+
+    for (actions, mut shooting, player_transform, collider) in &mut player_query {
+        if ingame_camera.kind != IngameCameraKind::FixedAngle {
+            shooting.target = None;
+            continue;
+        }
+        let up = player_transform.up();
+        let feet = player_transform.translation - up * collider.raw.compute_local_aabb().maxs.y;
+        let stick = actions
+            .axis_pair(PlayerAction::AimDirection)
+            .context("Player aim direction is not an axis pair")?
+            .max_normalized();
+
+        let target = if let Some(stick) = stick {
+            let direction =
+                get_camera_relative_direction(stick, ingame_camera, camera_transform, up);
+            Some(feet + direction.normalize_or_zero() * STICK_AIM_DISTANCE)
+        } else {
+            cursor
+                .and_then(|cursor| camera.viewport_to_world(camera_global_transform, cursor))
+                .and_then(|ray| {
+                    // Intersect the ray with the plane the player is standing on
+                    let denominator = ray.direction.dot(up);
+                    if denominator.abs() < 1e-5 {
+                        return None;
+                    }
+                    let distance = (feet - ray.origin).dot(up) / denominator;
+                    (distance > 0.0).then(|| ray.get_point(distance))
+                })
+        };
+        // Without a cursor or stick input, keep shooting straight ahead
+        shooting.target = Some(
+            target.unwrap_or_else(|| feet + player_transform.forward() * STICK_AIM_DISTANCE),
+        );
+    }
+    Ok(())
+}
+
+/// Runs after [`GeneralMovementSystemSet`] so that the player keeps facing the aim point instead of the direction it walks in.
+fn face_aim_target(mut player_query: Query<(&Shooting, &mut Transform), With<Player>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("face_aim_target").entered();
+    for (shooting, mut transform) in &mut player_query {
+        let Some(target) = shooting.target else {
+            continue;
+        };
+        let up = transform.up();
+        if (target - transform.translation).split(up).horizontal.is_approx_zero() {
+            continue;
+        }
+        *transform = transform.horizontally_looking_at(target, up);
+    }
+}
+
+fn spawn_reticle(
+    mut commands: Commands,
+    reticles: Query<(), With<AimReticle>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_reticle").entered();
+    if !reticles.is_empty() {
+        return;
+    }
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Torus {
+                radius: 0.25,
+                ring_radius: 0.02,
+                ..default()
+            })),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(1.0, 0.3, 0.2),
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        NotShadowCaster,
+        Name::new("Aim Reticle"),
+        AimReticle,
+    ));
+}
+
+fn move_reticle(
+    player_query: Query<(&Shooting, &Transform), With<Player>>,
+    mut reticle_query: Query<
+        (&mut Transform, &mut Visibility),
+        (With<AimReticle>, Without<Player>),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_reticle").entered();
+    let target = player_query.iter().next().and_then(|(shooting, transform)| {
+        shooting.target.map(|target| (target, transform.up()))
+    });
+    for (mut reticle_transform, mut visibility) in &mut reticle_query {
+        let Some((target, up)) = target else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        reticle_transform.translation = target + up * RETICLE_HEIGHT;
+        reticle_transform.rotation = Quat::from_rotation_arc(Vec3::Y, up);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::GameState;
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Rate at which the simulation advances, independent of the frame rate
+pub(crate) const TICKS_PER_SECOND: f64 = 60.0;
+
+/// Runs movement, combat, projectiles and AI at a fixed rate in [`CoreSchedule::FixedUpdate`], so that the same inputs produce the same outcome
+/// regardless of the frame rate. Systems that belong to the simulation are added to that schedule in one of the [`SimulationSet`]s,
+/// use [`FixedTime::period`] instead of [`Time::delta_seconds`], and draw their randomness from [`SimulationRng`].
+/// Rendering still happens every frame, so the transforms of simulated entities are interpolated between the last two ticks,
+/// see [`InterpolatedTransform`]. While [`SimulationFrozen`] is present, ticks still happen but nothing in them runs.
+pub(crate) fn simulation_plugin(app: &mut App) {
+    app.register_type::<InterpolatedTransform>()
+        .insert_resource(FixedTime::new_from_secs((1.0 / TICKS_PER_SECOND) as f32))
+        .init_resource::<SimulationRng>()
+        .init_resource::<SimulationTick>()
+        .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+            // Systems that are not explicitly ordered could otherwise run in a different order every tick
+            schedule
+                .set_executor_kind(ExecutorKind::SingleThreaded)
+                .configure_sets(
+                    (
+                        SimulationSet::Restore,
+                        SimulationSet::Input,
+                        SimulationSet::Gameplay,
+                        SimulationSet::Store,
+                    )
+                        .chain(),
+                )
+                .configure_set(SimulationSet::Restore.run_if(is_simulation_running))
+                .configure_set(
+                    SimulationSet::Input
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(is_simulation_running),
+                )
+                .configure_set(
+                    SimulationSet::Gameplay
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(is_simulation_running),
+                )
+                .configure_set(SimulationSet::Store.run_if(is_simulation_running));
+        })
+        .add_system(reset_simulation.in_schedule(OnEnter(GameState::Playing)))
+        .add_systems(
+            (advance_tick, add_interpolation, restore_simulated_transforms)
+                .chain()
+                .in_set(SimulationSet::Restore)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(
+            store_simulated_transforms
+                .in_set(SimulationSet::Store)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(interpolate_transforms.in_set(InterpolationSystemSet));
+}
+
+/// The stages of a single simulation tick in [`CoreSchedule::FixedUpdate`], in order.
+/// The physics step runs between [`SimulationSet::Gameplay`] and [`SimulationSet::Store`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub(crate) enum SimulationSet {
+    /// Undoes the interpolation of the last frame so that the simulation continues from its own state
+    Restore,
+    /// Turns the player's input into requests like [`Walking::direction`](crate::movement::general_movement::Walking::direction)
+    Input,
+    /// Movement, combat, projectiles and AI
+    Gameplay,
+    /// Remembers the outcome of this tick for the interpolation
+    Store,
+}
+
+/// Interpolates the [`Transform`]s of simulated entities for rendering. Runs every frame in [`CoreSet::Update`], after all ticks of the frame,
+/// so systems that follow a simulated entity, like the camera, should read its [`Transform`] after this.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub(crate) struct InterpolationSystemSet;
+
+/// Stops the simulation in its tracks, e.g. so that a level can finish loading before a replay starts on an exact tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct SimulationFrozen;
+
+pub(crate) fn is_simulation_running(frozen: Option<Res<SimulationFrozen>>) -> bool {
+    frozen.is_none()
+}
+
+/// The number of ticks simulated since the level was entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub(crate) struct SimulationTick(pub(crate) u64);
+
+/// The only source of randomness the simulation may use. It is reset to [`SimulationRng::seed`] whenever a level is entered,
+/// so a playthrough can be reproduced from its seed and inputs.
+#[derive(Debug, Clone, Resource)]
+pub(crate) struct SimulationRng {
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl SimulationRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Smooths the rendered movement of a simulated entity. Added automatically to every non-fixed [`RigidBody`],
+/// other simulated entities that move by themselves need to be spawned with it.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub(crate) struct InterpolatedTransform {
+    /// State at the end of the second to last tick
+    pub(crate) previous: Transform,
+    /// State at the end of the last tick
+    pub(crate) current: Transform,
+    /// What was last shown, used to detect teleports from outside the simulation
+    pub(crate) rendered: Transform,
+}
+
+impl From<Transform> for InterpolatedTransform {
+    fn from(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+            rendered: transform,
+        }
+    }
+}
+
+fn reset_simulation(mut rng: ResMut<SimulationRng>, mut tick: ResMut<SimulationTick>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_simulation").entered();
+    let seed = rng.seed();
+    rng.reseed(seed);
+    tick.0 = 0;
+    info!("Simulation seed: {seed}");
+}
+
+fn advance_tick(mut tick: ResMut<SimulationTick>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("advance_tick").entered();
+    tick.0 += 1;
+}
+
+fn add_interpolation(
+    mut commands: Commands,
+    bodies: Query<
+        (Entity, &Transform, &RigidBody),
+        (Added<RigidBody>, Without<InterpolatedTransform>),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("add_interpolation").entered();
+    for (entity, transform, rigid_body) in &bodies {
+        if *rigid_body == RigidBody::Fixed {
+            continue;
+        }
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(InterpolatedTransform::from(*transform));
+        }
+    }
+}
+
+fn restore_simulated_transforms(
+    mut entities: Query<(&mut Transform, &mut InterpolatedTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("restore_simulated_transforms").entered();
+    for (mut transform, mut interpolated) in &mut entities {
+        if *transform == interpolated.rendered {
+            *transform = interpolated.current;
+        } else {
+            // Something outside the simulation, e.g. loading a save, has put the entity somewhere else
+            *interpolated = InterpolatedTransform::from(*transform);
+        }
+    }
+}
+
+fn store_simulated_transforms(mut entities: Query<(&Transform, &mut InterpolatedTransform)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("store_simulated_transforms").entered();
+    for (transform, mut interpolated) in &mut entities {
+        interpolated.previous = interpolated.current;
+        interpolated.current = *transform;
+        interpolated.rendered = *transform;
+    }
+}
+
+fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut entities: Query<(&mut Transform, &mut InterpolatedTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("interpolate_transforms").entered();
+    let alpha =
+        (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).clamp(0.0, 1.0);
+    for (mut transform, mut interpolated) in &mut entities {
+        if *transform != interpolated.rendered {
+            // Moved outside the simulation, so there is nothing to interpolate
+            continue;
+        }
+        let previous = interpolated.previous;
+        let current = interpolated.current;
+        let rendered = Transform {
+            translation: previous.translation.lerp(current.translation, alpha),
+            rotation: previous.rotation.slerp(current.rotation, alpha),
+            scale: previous.scale.lerp(current.scale, alpha),
+        };
+        *transform = rendered;
+        interpolated.rendered = rendered;
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::asset::{Assets, Handle};
 use bevy::ecs::component::Component;
 use bevy::prelude::{
-    Bundle, Commands, Entity, GlobalTransform, Query, Res, ResMut, Resource, With,
+    default, Bundle, Commands, Entity, GlobalTransform, Query, Res, ResMut, Resource, With,
 };
 use bevy::transform::TransformBundle;
 use bevy_kira_audio::{Audio, AudioInstance, AudioSource, AudioTween};
@@ -31,6 +31,7 @@ impl DisposableAudioEmitterBundle {
         Self {
             emitter_handle: AudioEmitterHandle {
                 instance: Some(instance),
+                ..default()
             },
             disposable_emitter: DisposableAudioEmitter::default(),
             tranform_bundle: transform_bundle,
@@ -42,12 +43,32 @@ impl DisposableAudioEmitterBundle {
 ///
 /// Add [`Handle<AudioInstance>`]s to control their pan and volume based on emitter
 /// and receiver positions.
-#[derive(Component, Default)]
+#[derive(Component)]
 pub struct AudioEmitterHandle {
     /// Audio instances that are played by this emitter
     ///
     /// The same instance should only be on one emitter.
     pub instance: Option<Handle<AudioInstance>>,
+    /// Loudness of the emitter, multiplied with the distance based volume.
+    pub volume: f32,
+}
+
+/// Lowers the volume of an emitter whose sound has to cross into another medium to reach the receiver,
+/// e.g. when it is above the surface while the receiver is underwater.
+/// `bevy_kira_audio` does not expose kira's filters, so the volume is lowered instead of cutting high frequencies.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Muffled {
+    /// Multiplied with the distance based volume
+    pub volume: f32,
+}
+
+impl Default for AudioEmitterHandle {
+    fn default() -> Self {
+        Self {
+            instance: None,
+            volume: 1.0,
+        }
+    }
 }
 
 #[derive(Component, Default)]
@@ -65,7 +86,10 @@ impl LoopAudioEmitterBundle {
         setup_fn: fn(Handle<AudioSource>, &Res<Audio>) -> Handle<AudioInstance>,
     ) -> Self {
         Self {
-            emitter_handle: AudioEmitterHandle { instance: None },
+            emitter_handle: AudioEmitterHandle {
+                instance: None,
+                ..default()
+            },
             loop_emitter: LoopAudioEmitter {
                 source_handle,
                 setup_fn,
@@ -248,15 +272,17 @@ impl SpatialAudio {
     pub(crate) fn update(
         &self,
         receiver_transform: &GlobalTransform,
-        emitters: &Query<(&GlobalTransform, &AudioEmitterHandle)>,
+        emitters: &Query<(&GlobalTransform, &AudioEmitterHandle, Option<&Muffled>)>,
         audio_instances: &mut Assets<AudioInstance>,
     ) {
         //this causes some odd behaviors because it only takes the right ear into account.
-        for (emitter_transform, emitter) in emitters {
+        for (emitter_transform, emitter, muffled) in emitters {
             let sound_path = emitter_transform.translation() - receiver_transform.translation();
             let volume = (1. - sound_path.length() / self.max_distance)
                 .clamp(0., 1.)
-                .powi(2);
+                .powi(2)
+                * emitter.volume
+                * muffled.map_or(1., |muffled| muffled.volume);
 
             let right_ear_angle = receiver_transform.right().angle_between(sound_path);
             let panning = (right_ear_angle.cos() + 1.) / 2.;
@@ -274,7 +300,7 @@ impl SpatialAudio {
 pub(crate) fn run_spatial_audio(
     spacial_audio: Res<SpatialAudio>,
     receiver: Query<&GlobalTransform, With<CustomAudioReceiver>>,
-    emitters: Query<(&GlobalTransform, &AudioEmitterHandle)>,
+    emitters: Query<(&GlobalTransform, &AudioEmitterHandle, Option<&Muffled>)>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
 ) {
     if let Some(receiver_transform) = receiver.iter().next() {
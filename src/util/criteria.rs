@@ -1,8 +1,8 @@
-use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::actions::{InputContext, InputContextStack};
 use bevy::prelude::*;
 
-pub(crate) fn is_frozen(actions_frozen: Res<ActionsFrozen>) -> bool {
-    actions_frozen.is_frozen()
+pub(crate) fn is_in_gameplay_context(input_contexts: Res<InputContextStack>) -> bool {
+    input_contexts.current() == InputContext::Gameplay
 }
 
 #[allow(unused)]
@@ -1,6 +1,7 @@
 use crate::file_system_interaction::asset_loading::DialogAssets;
 use crate::file_system_interaction::config::GameConfig;
-use crate::player_control::actions::{ActionsFrozen, PlayerAction};
+use crate::player_control::actions::{InputContext, InputContextStack, PlayerAction};
+use crate::player_control::camera::CinematicEvent;
 use crate::world_interaction::condition::{ActiveConditions, ConditionAddEvent, ConditionId};
 use crate::world_interaction::dialog::resources::Page;
 pub(crate) use crate::world_interaction::dialog::resources::{
@@ -40,7 +41,7 @@ fn set_current_dialog(
     mut dialog_events: EventReader<DialogEvent>,
     dialogs: Res<Assets<Dialog>>,
     dialog_handles: Res<DialogAssets>,
-    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut input_contexts: ResMut<InputContextStack>,
 ) -> Result<()> {
     for dialog_event in dialog_events.iter() {
         let path = Path::new("dialogs")
@@ -89,7 +90,7 @@ fn set_current_dialog(
             current_page,
             last_choice: None,
         });
-        actions_frozen.freeze();
+        input_contexts.push(InputContext::Dialog);
     }
     Ok(())
 }
@@ -100,8 +101,9 @@ fn show_dialog(
     current_dialog: Option<ResMut<CurrentDialog>>,
     active_conditions: Res<ActiveConditions>,
     mut condition_writer: EventWriter<ConditionAddEvent>,
+    mut cinematic_writer: EventWriter<CinematicEvent>,
     mut egui_contexts: EguiContexts,
-    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut input_contexts: ResMut<InputContextStack>,
     actions: Query<&ActionState<PlayerAction>>,
     time: Res<Time>,
     mut elapsed_time: Local<f32>,
@@ -136,7 +138,8 @@ fn show_dialog(
                             &mut current_dialog,
                             &active_conditions,
                             &mut condition_writer,
-                            &mut actions_frozen,
+                            &mut cinematic_writer,
+                            &mut input_contexts,
                             actions,
                             current_page.next_page,
                             &mut elapsed_time,
@@ -166,7 +169,8 @@ fn present_choices(
     current_dialog: &mut CurrentDialog,
     active_conditions: &ActiveConditions,
     condition_writer: &mut EventWriter<ConditionAddEvent>,
-    actions_frozen: &mut ActionsFrozen,
+    cinematic_writer: &mut EventWriter<CinematicEvent>,
+    input_contexts: &mut InputContextStack,
     actions: &ActionState<PlayerAction>,
     next_page: NextPage,
     elapsed_time: &mut f32,
@@ -211,7 +215,8 @@ fn present_choices(
                 current_dialog,
                 active_conditions,
                 condition_writer,
-                actions_frozen,
+                cinematic_writer,
+                input_contexts,
                 actions,
                 next_page,
                 elapsed_time,
@@ -220,8 +225,11 @@ fn present_choices(
         NextPage::Exit => {
             let text = create_choice_rich_text(0, "Exit");
             if ui.button(text).clicked() || actions.just_pressed(PlayerAction::numbered_choice(1)) {
+                if let Some(cinematic) = current_dialog.fetch_current_page()?.cinematic {
+                    cinematic_writer.send(CinematicEvent(cinematic));
+                }
                 commands.remove_resource::<CurrentDialog>();
-                actions_frozen.unfreeze();
+                input_contexts.pop(InputContext::Dialog);
             }
         }
     }
@@ -1,3 +1,4 @@
+use crate::player_control::camera::Cinematic;
 use crate::world_interaction::condition::{ActiveConditions, ConditionId};
 use anyhow::{Context, Result};
 use bevy::prelude::*;
@@ -65,6 +66,9 @@ pub(crate) struct Page {
     #[serde(default = "get_default_talking_speed")]
     pub(crate) talking_speed: f32,
     pub(crate) next_page: NextPage,
+    /// Played when the dialog is exited from this page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cinematic: Option<Cinematic>,
 }
 
 fn get_default_talking_speed() -> f32 {
@@ -77,6 +81,7 @@ impl Default for Page {
             text: default(),
             talking_speed: get_default_talking_speed(),
             next_page: default(),
+            cinematic: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate::player_control::actions::PlayerAction;
 use crate::player_control::camera::{IngameCamera, IngameCameraKind};
 use crate::player_control::player_embodiment::Player;
-use crate::util::criteria::is_frozen;
+use crate::util::criteria::is_in_gameplay_context;
 use crate::world_interaction::dialog::{DialogEvent, DialogTarget};
 use crate::GameState;
 use anyhow::{Context, Result};
@@ -25,7 +25,7 @@ pub(crate) fn interactions_ui_plugin(app: &mut App) {
         )
         .add_system(
             display_interaction_prompt
-                .run_if(resource_exists::<InteractionUi>().and_then(not(is_frozen)))
+                .run_if(resource_exists::<InteractionUi>().and_then(is_in_gameplay_context))
                 .in_set(OnUpdate(GameState::Playing)),
         );
 }